@@ -0,0 +1,134 @@
+use bytes::BytesMut;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use maui::{
+    models::Event,
+    protocol::{Codec, Packet, Word, WordDecoding},
+};
+use tokio_util::codec::{Decoder, Encoder};
+
+fn encode(packet: Packet) -> BytesMut {
+    let mut buf = BytesMut::new();
+    Codec::new().encode(packet, &mut buf).unwrap();
+    buf
+}
+
+fn on_kill() -> Packet {
+    let words = [
+        "player.onKill",
+        "Attacker",
+        "Victim",
+        "U/AEK971/AEK971",
+        "true",
+    ];
+    Packet::new(
+        7,
+        false,
+        true,
+        words.iter().map(|&w| Word::from(w)).collect(),
+    )
+}
+
+/// Response to `admin.listPlayers all` on a full 64 player server.
+fn list_players() -> Packet {
+    let mut words: Vec<Word> = vec!["OK", "10", "name", "guid", "teamId", "squadId", "kills"]
+        .into_iter()
+        .chain(vec!["deaths", "score", "rank", "ping", "type", "64"])
+        .map(Word::from)
+        .collect();
+
+    for i in 0..64 {
+        words.push(Word::from(format!("Player{}", i)));
+        words.push(Word::from(format!("EA_{:032X}", i)));
+        for field in &["1", "3", "12", "4", "2350", "140", "42", "0"] {
+            words.push(Word::from(*field));
+        }
+    }
+
+    Packet::new(7, true, false, words)
+}
+
+fn bench_decode(c: &mut Criterion, name: &str, packet: Packet) {
+    let encoded = encode(packet);
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+    group.bench_function("decode", |b| {
+        b.iter(|| {
+            let mut buf = encoded.clone();
+            black_box(Codec::new().decode(&mut buf).unwrap().unwrap())
+        })
+    });
+
+    group.bench_function("decode_str", |b| {
+        b.iter(|| {
+            let mut buf = encoded.clone();
+            let packet = Codec::new().decode(&mut buf).unwrap().unwrap();
+            for word in &packet.words {
+                black_box(word.to_str().unwrap());
+            }
+        })
+    });
+
+    // Copies every word into an owned string, as decoding did before words shared the buffer.
+    group.bench_function("decode_owned", |b| {
+        b.iter(|| {
+            let mut buf = encoded.clone();
+            let packet = Codec::new().decode(&mut buf).unwrap().unwrap();
+            let words: Vec<String> = packet
+                .words
+                .iter()
+                .map(|word| word.to_str().unwrap().to_owned())
+                .collect();
+            black_box(words)
+        })
+    });
+
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    bench_decode(c, "on_kill", on_kill());
+    bench_decode(c, "list_players", list_players());
+}
+
+/// Reads and parses an event the way the connection does, from words borrowed from the buffer.
+fn parse_events(c: &mut Criterion) {
+    let encoded = encode(on_kill());
+
+    c.bench_function("on_kill/event", |b| {
+        b.iter(|| {
+            let mut buf = encoded.clone();
+            let packet = Codec::new().decode(&mut buf).unwrap().unwrap();
+            let words: Vec<_> = packet
+                .words
+                .iter()
+                .map(|word| WordDecoding::Latin1.decode_str(word).unwrap())
+                .collect();
+            black_box(Event::from_words(&words).unwrap())
+        })
+    });
+
+    // Copies every word into an owned string before parsing, as the connection used to.
+    c.bench_function("on_kill/event_owned", |b| {
+        b.iter(|| {
+            let mut buf = encoded.clone();
+            let packet = Codec::new().decode(&mut buf).unwrap().unwrap();
+            let words: Vec<_> = packet
+                .words
+                .iter()
+                .map(|word| WordDecoding::Latin1.decode(word).unwrap())
+                .collect();
+            black_box(Event::from_words(&words).unwrap())
+        })
+    });
+}
+
+fn encode_packets(c: &mut Criterion) {
+    let packet = list_players();
+    c.bench_function("list_players/encode", |b| {
+        b.iter(|| black_box(encode(packet.clone())))
+    });
+}
+
+criterion_group!(benches, decode, parse_events, encode_packets);
+criterion_main!(benches);
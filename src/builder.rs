@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use tokio::{
+    net::{self, ToSocketAddrs},
+    sync::broadcast,
+};
+
+use crate::{
+    connection::{request_channel, Config},
+    protocol::WordDecoding,
+    rate_limit::{RateLimit, RateLimiter},
+    reconnect::{self, ConnectionState, ReconnectPolicy},
+    Client, Connection, Error, EventSender,
+};
+
+/// Configures and connects a [`Client`].
+#[derive(Clone, Debug)]
+pub struct ClientBuilder {
+    config: Config,
+    event_capacity: usize,
+    request_queue: Option<usize>,
+    request_timeout: Option<Duration>,
+    rate_limit: Option<RateLimit>,
+    chat_rate_limit: Option<RateLimit>,
+    reconnect: Option<ReconnectPolicy>,
+}
+
+impl ClientBuilder {
+    pub fn new() -> ClientBuilder {
+        ClientBuilder {
+            config: Config::default(),
+            event_capacity: 1000,
+            request_queue: None,
+            request_timeout: None,
+            rate_limit: None,
+            chat_rate_limit: None,
+            reconnect: None,
+        }
+    }
+
+    /// Number of events buffered for every subscriber before the slowest one starts lagging.
+    ///
+    /// Defaults to 1000.
+    pub fn event_capacity(mut self, capacity: usize) -> ClientBuilder {
+        self.event_capacity = capacity;
+        self
+    }
+
+    /// Number of requests that may be queued before sending waits for the connection to catch
+    /// up, or `None` for an unbounded queue.
+    ///
+    /// Waiting for room in the queue counts towards the request timeout.
+    ///
+    /// Defaults to `None`.
+    pub fn request_queue(mut self, bound: Option<usize>) -> ClientBuilder {
+        self.request_queue = bound.map(|bound| bound.max(1));
+        self
+    }
+
+    /// Number of requests that may await their response at once, or `None` for no limit.
+    ///
+    /// Further requests stay queued until a response arrives, so that bursts of requests don't
+    /// overwhelm the remote host.
+    ///
+    /// Defaults to `None`.
+    pub fn max_in_flight(mut self, max: Option<usize>) -> ClientBuilder {
+        self.config.max_in_flight = max.map(|max| max.max(1));
+        self
+    }
+
+    /// Sends a `version` request every interval while connected, declaring the connection lost
+    /// once `max_missed` of them in a row go unanswered, or `None` to disable the heartbeat.
+    ///
+    /// This detects half-open connections, which would otherwise never be reported as closed.
+    ///
+    /// Defaults to `None`.
+    pub fn heartbeat(mut self, interval: Option<Duration>, max_missed: u32) -> ClientBuilder {
+        self.config.heartbeat_interval = interval.filter(|interval| !interval.is_zero());
+        self.config.heartbeat_max_missed = max_missed.max(1);
+        self
+    }
+
+    /// Time allowed to establish the TCP connection, failing with
+    /// [`ErrorKind::Timeout`](crate::ErrorKind::Timeout) once it elapses.
+    ///
+    /// Defaults to `None`.
+    pub fn connect_timeout(mut self, timeout: Option<Duration>) -> ClientBuilder {
+        self.config.connect_timeout = timeout;
+        self
+    }
+
+    /// Whether `SO_KEEPALIVE` is set on the socket.
+    ///
+    /// Defaults to `false`.
+    pub fn tcp_keepalive(mut self, keepalive: bool) -> ClientBuilder {
+        self.config.tcp_keepalive = keepalive;
+        self
+    }
+
+    /// Whether `TCP_NODELAY` is set on the socket.
+    ///
+    /// Defaults to `false`.
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> ClientBuilder {
+        self.config.tcp_nodelay = nodelay;
+        self
+    }
+
+    /// Logs in with the hashed login as soon as the connection has been established.
+    pub fn login(mut self, password: String) -> ClientBuilder {
+        self.config.password = Some(password);
+        self
+    }
+
+    /// Whether events are enabled as soon as the connection has been established.
+    ///
+    /// Defaults to `false`.
+    pub fn events_enable(mut self, enable: bool) -> ClientBuilder {
+        self.config.events_enable = enable;
+        self
+    }
+
+    /// Largest packet in bytes that may be sent to or received from the remote host.
+    ///
+    /// Defaults to 16384, the limit documented by the protocol.
+    pub fn max_packet_size(mut self, size: usize) -> ClientBuilder {
+        self.config.max_packet_size = size;
+        self
+    }
+
+    /// Whether events sent by the remote host are acknowledged with an `OK` response.
+    ///
+    /// Defaults to `true`.
+    pub fn acknowledge_events(mut self, acknowledge: bool) -> ClientBuilder {
+        self.config.ack_events = acknowledge;
+        self
+    }
+
+    /// How words that aren't valid UTF-8 are decoded.
+    ///
+    /// Defaults to [`WordDecoding::Latin1`].
+    pub fn word_decoding(mut self, decoding: WordDecoding) -> ClientBuilder {
+        self.config.word_decoding = decoding;
+        self
+    }
+
+    /// Whether known events that can't be parsed are delivered as
+    /// [`Event::Unknown`](crate::models::Event::Unknown) instead of closing the connection with
+    /// [`ErrorKind::Parse`](crate::ErrorKind::Parse).
+    ///
+    /// Events this crate doesn't know about are always delivered as unknown events.
+    ///
+    /// Defaults to `true`.
+    pub fn lenient_events(mut self, lenient: bool) -> ClientBuilder {
+        self.config.lenient_events = lenient;
+        self
+    }
+
+    /// Default timeout applied to requests, see [`Client::set_timeout`].
+    ///
+    /// Defaults to `None`.
+    pub fn request_timeout(mut self, timeout: Option<Duration>) -> ClientBuilder {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Rate limit applied to every request except for chat messages.
+    ///
+    /// Waiting for the rate limit counts towards the request timeout.
+    ///
+    /// Defaults to `None`.
+    pub fn rate_limit(mut self, limit: Option<RateLimit>) -> ClientBuilder {
+        self.rate_limit = limit;
+        self
+    }
+
+    /// Rate limit applied to chat messages, sent with `admin.say` or `admin.yell`.
+    ///
+    /// Defaults to `None`.
+    pub fn chat_rate_limit(mut self, limit: Option<RateLimit>) -> ClientBuilder {
+        self.chat_rate_limit = limit;
+        self
+    }
+
+    /// Re-establishes the connection according to the policy whenever it gets lost.
+    ///
+    /// Requests made while reconnecting are sent once the session has been restored, while
+    /// requests in flight at the time of the disconnect fail with
+    /// [`ErrorKind::ConnectionLost`](crate::ErrorKind::ConnectionLost). The client is closed
+    /// once the policy gives up.
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> ClientBuilder {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    pub async fn connect<A: ToSocketAddrs>(self, addr: A) -> Result<Client, Error> {
+        let addrs: Vec<_> = net::lookup_host(addr).await?.collect();
+        let event_tx = EventSender::new(self.event_capacity);
+        let (state_tx, _) = broadcast::channel(16);
+        let (request_tx, mut request_rx) = request_channel(self.request_queue);
+        let connection = Connection::connect(&addrs, &self.config, event_tx.clone()).await?;
+
+        match self.reconnect {
+            Some(policy) => {
+                tokio::spawn(reconnect::run(
+                    addrs,
+                    self.config,
+                    policy,
+                    connection,
+                    event_tx.clone(),
+                    state_tx.clone(),
+                    request_rx,
+                ));
+            }
+            None => {
+                let disconnected_tx = state_tx.clone();
+                tokio::spawn(async move {
+                    if connection.run(&mut request_rx).await.is_err() {
+                        let _ = disconnected_tx.send(ConnectionState::Disconnected);
+                    }
+                });
+            }
+        }
+
+        Ok(Client::new(
+            event_tx,
+            state_tx,
+            request_tx,
+            RateLimiter::new(self.chat_rate_limit, self.rate_limit),
+            self.request_timeout,
+        ))
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+}
@@ -1,31 +1,55 @@
+use std::{sync::Arc, time::Duration};
+
 use tokio::{
-    net::{TcpStream, ToSocketAddrs},
-    sync::{broadcast, mpsc, oneshot},
+    net::ToSocketAddrs,
+    sync::{broadcast, oneshot},
+    time,
 };
 
 use crate::{
-    models::{Event, PlayerInfo, ServerInfo, Subset},
-    Connection, Error, Request,
+    connection::{into_result, RequestTx},
+    models::{
+        BanEntry, BanTimeout, GameAdmin, IdType, PlayerInfo, ServerConfig, ServerInfo, ServerType,
+        Subset, Var, VarName,
+    },
+    rate_limit::RateLimiter,
+    ClientBuilder, ConnectionState, Error, ErrorKind, EventEnvelope, EventSender, EventStream,
+    Message,
 };
 
 #[derive(Clone, Debug)]
 pub struct Client {
-    event_tx: broadcast::Sender<Event>,
-    request_tx: mpsc::UnboundedSender<Request>,
+    event_tx: EventSender,
+    state_tx: broadcast::Sender<ConnectionState>,
+    request_tx: RequestTx,
+    rate_limiter: Arc<RateLimiter>,
+    timeout: Option<Duration>,
 }
 
 impl Client {
     pub async fn connect<A: ToSocketAddrs>(addr: A) -> Result<Client, Error> {
-        let tcp_stream = TcpStream::connect(addr).await?;
-        let (event_tx, _) = broadcast::channel(1000);
-        let (request_tx, request_rx) = mpsc::unbounded_channel();
-        let connection = Connection::new(tcp_stream, event_tx.clone(), request_rx);
-        tokio::spawn(connection.run());
+        ClientBuilder::new().connect(addr).await
+    }
 
-        Ok(Client {
+    /// Returns a builder to configure the client before connecting.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    pub(crate) fn new(
+        event_tx: EventSender,
+        state_tx: broadcast::Sender<ConnectionState>,
+        request_tx: RequestTx,
+        rate_limiter: RateLimiter,
+        timeout: Option<Duration>,
+    ) -> Client {
+        Client {
             event_tx,
+            state_tx,
             request_tx,
-        })
+            rate_limiter: Arc::new(rate_limiter),
+            timeout,
+        }
     }
 
     /// Completes when the connection to the remote host has been lost or terminated.
@@ -38,28 +62,73 @@ impl Client {
         self.request_tx.is_closed()
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+    pub fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
         self.event_tx.subscribe()
     }
 
+    /// Returns a stream of the events sent by the remote host from now on.
+    pub fn events(&self) -> EventStream {
+        EventStream::new(self.event_tx.subscribe())
+    }
+
+    /// Subscribes to changes of the connection to the remote host.
+    pub fn subscribe_state(&self) -> broadcast::Receiver<ConnectionState> {
+        self.state_tx.subscribe()
+    }
+
+    /// Returns the timeout applied to requests made through this client.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets the timeout applied to requests made through this client, not affecting its clones.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
     pub async fn send(&self, words: Vec<String>) -> Result<Vec<String>, Error> {
-        let (response_tx, response_rx) = oneshot::channel();
-        if self.request_tx.send((words, response_tx)).is_err() {
-            return Err(Error::new_connection_lost());
+        self.send_inner(words, self.timeout).await
+    }
+
+    /// Sends a request, failing with [`ErrorKind::Timeout`] if no response arrives in time.
+    pub async fn send_timeout(
+        &self,
+        words: Vec<String>,
+        timeout: Duration,
+    ) -> Result<Vec<String>, Error> {
+        self.send_inner(words, Some(timeout)).await
+    }
+
+    async fn send_inner(
+        &self,
+        words: Vec<String>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<String>, Error> {
+        // The timeout also covers waiting for the rate limit and for room in the request queue.
+        match timeout {
+            Some(timeout) => match time::timeout(timeout, self.request(words)).await {
+                Ok(result) => result,
+                Err(_) => Err(Error::new_timeout()),
+            },
+            None => self.request(words).await,
         }
+    }
+
+    async fn request(&self, words: Vec<String>) -> Result<Vec<String>, Error> {
+        self.rate_limiter.acquire(&words).await;
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.request_tx.send((words, response_tx)).await?;
 
         match response_rx.await {
-            Ok(res) => {
-                if &res.0 == "OK" {
-                    Ok(res.1)
-                } else {
-                    Err(Error::new_status(res.0))
-                }
-            }
+            Ok(response) => into_result(response),
             Err(_) => Err(Error::new_connection_lost()),
         }
     }
 
+    /// Logs in by sending the password in clear text.
+    ///
+    /// Prefer [`Client::login_hashed`], which never exposes the password on the wire.
     pub async fn login(&self, password: String) -> Result<(), Error> {
         self.send(vec!["login.plainText".to_owned(), password])
             .await?;
@@ -67,6 +136,51 @@ impl Client {
         Ok(())
     }
 
+    /// Logs in using the salted password hash challenge.
+    pub async fn login_hashed(&self, password: String) -> Result<(), Error> {
+        let mut words = self
+            .send(vec!["login.hashed".to_owned()])
+            .await?
+            .into_iter();
+
+        let hash = hash_password(&next!(words), &password)?;
+        self.send(vec!["login.hashed".to_owned(), hash]).await?;
+
+        Ok(())
+    }
+
+    /// Logs out, leaving the connection open.
+    pub async fn logout(&self) -> Result<(), Error> {
+        self.send(vec!["logout".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    /// Ends the session gracefully, affecting every clone of this client.
+    ///
+    /// Requests made before the shutdown are still sent and answered, while later ones fail
+    /// with [`ErrorKind::ConnectionLost`]. Afterwards `quit` is sent and the connection
+    /// is closed, so that [`Client::closed`] has completed by the time this returns.
+    ///
+    /// If this times out, or is dropped, the connection is closed without waiting any longer.
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.request_tx
+            .send_message(Message::Shutdown(done_tx))
+            .await?;
+
+        let result = match self.timeout {
+            Some(timeout) => match time::timeout(timeout, done_rx).await {
+                Ok(result) => result,
+                Err(_) => return Err(Error::new_timeout()),
+            },
+            None => done_rx.await,
+        };
+        self.request_tx.closed().await;
+
+        result.map_err(|_| Error::new_connection_lost())
+    }
+
     pub async fn events_enable(&self) -> Result<(), Error> {
         self.send(vec!["admin.eventsEnabled".to_owned(), true.to_string()])
             .await?;
@@ -90,31 +204,11 @@ impl Client {
 
     /// Returns list of all players currently on the server.
     pub async fn players_get(&self) -> Result<Vec<PlayerInfo>, Error> {
-        let mut words = self
+        let words = self
             .send(vec!["admin.listPlayers".to_owned(), "all".to_owned()])
-            .await?
-            .into_iter();
-
-        let offset: usize = next_parse!(words);
-        words.nth(offset - 1);
-        let num_of_players: usize = next_parse!(words);
-        let mut players = Vec::with_capacity(num_of_players);
-        for _ in 0..num_of_players {
-            players.push(PlayerInfo {
-                name: next!(words),
-                guid: next!(words),
-                team_id: next_parse!(words),
-                squad_id: next_parse!(words),
-                kills: next_parse!(words),
-                deaths: next_parse!(words),
-                score: next_parse!(words),
-                rank: next_parse!(words),
-                ping: next_parse!(words),
-                kind: next_parse!(words),
-            });
-        }
+            .await?;
 
-        Ok(players)
+        PlayerInfo::from_block(&mut words.iter().map(String::as_str))
     }
 
     pub async fn say(&self, message: String, subset: Subset) -> Result<(), Error> {
@@ -174,15 +268,21 @@ impl Client {
     pub async fn maps_get(&self) -> Result<Vec<(String, String, usize)>, Error> {
         let mut maps = Vec::new();
         let mut offset: usize = 0;
-        let mut words = self
-            .send(vec!["mapList.list".to_owned(), offset.to_string()])
-            .await?
-            .into_iter();
 
         loop {
+            let mut words = self
+                .send(vec!["mapList.list".to_owned(), offset.to_string()])
+                .await?
+                .into_iter();
+
             let num_of_maps: usize = next_parse!(words);
-            assert_eq!(next!(words), "3");
-            maps.reserve(num_of_maps);
+            let words_per_map = next!(words);
+            if words_per_map != "3" {
+                return Err(Error::new_parse(format!(
+                    "invalid number of words per map: {}",
+                    words_per_map
+                )));
+            }
 
             for _ in 0..num_of_maps {
                 maps.push((next!(words), next!(words), next_parse!(words)));
@@ -196,6 +296,22 @@ impl Client {
         }
     }
 
+    /// Adds a map to the map list, appending it unless an index is specified.
+    pub async fn maps_add(
+        &self,
+        map: String,
+        gamemode: String,
+        rounds: usize,
+        index: Option<usize>,
+    ) -> Result<(), Error> {
+        let mut words = vec!["mapList.add".to_owned(), map, gamemode, rounds.to_string()];
+        words.extend(index.map(|i| i.to_string()));
+
+        self.send(words).await?;
+
+        Ok(())
+    }
+
     pub async fn maps_remove(&self, index: usize) -> Result<(), Error> {
         self.send(vec!["mapList.remove".to_owned(), index.to_string()])
             .await?;
@@ -261,4 +377,408 @@ impl Client {
 
         Ok(())
     }
+
+    /// Adds a ban, replacing any previous ban for the same id.
+    pub async fn bans_add(
+        &self,
+        id_type: IdType,
+        id: String,
+        timeout: BanTimeout,
+        reason: Option<String>,
+    ) -> Result<(), Error> {
+        let mut words = vec!["banList.add".to_owned(), id_type.as_str().to_owned(), id];
+        words.extend(timeout.into_words());
+        words.extend(reason);
+
+        self.send(words).await?;
+
+        Ok(())
+    }
+
+    pub async fn bans_remove(&self, id_type: IdType, id: String) -> Result<(), Error> {
+        self.send(vec![
+            "banList.remove".to_owned(),
+            id_type.as_str().to_owned(),
+            id,
+        ])
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn bans_clear(&self) -> Result<(), Error> {
+        self.send(vec!["banList.clear".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    /// Returns all entries of the ban list.
+    pub async fn bans_get(&self) -> Result<Vec<BanEntry>, Error> {
+        let mut bans = Vec::new();
+        let mut offset: usize = 0;
+
+        loop {
+            let mut words = self
+                .send(vec!["banList.list".to_owned(), offset.to_string()])
+                .await?
+                .into_iter()
+                .peekable();
+
+            let mut num_of_bans = 0;
+            while words.peek().is_some() {
+                bans.push(BanEntry::from_words(&mut words)?);
+                num_of_bans += 1;
+            }
+
+            if num_of_bans >= 100 {
+                offset += 100;
+            } else {
+                return Ok(bans);
+            }
+        }
+    }
+
+    pub async fn bans_load(&self) -> Result<(), Error> {
+        self.send(vec!["banList.load".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    pub async fn bans_save(&self) -> Result<(), Error> {
+        self.send(vec!["banList.save".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    pub async fn reserved_slots_add(&self, name: String) -> Result<(), Error> {
+        self.send(vec!["reservedSlotsList.add".to_owned(), name])
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn reserved_slots_remove(&self, name: String) -> Result<(), Error> {
+        self.send(vec!["reservedSlotsList.remove".to_owned(), name])
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn reserved_slots_clear(&self) -> Result<(), Error> {
+        self.send(vec!["reservedSlotsList.clear".to_owned()])
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns names of all players on the reserved slots list.
+    pub async fn reserved_slots_get(&self) -> Result<Vec<String>, Error> {
+        let mut names = Vec::new();
+        let mut offset: usize = 0;
+
+        loop {
+            let words = self
+                .send(vec![
+                    "reservedSlotsList.list".to_owned(),
+                    offset.to_string(),
+                ])
+                .await?;
+
+            let num_of_names = words.len();
+            names.extend(words);
+
+            if num_of_names >= 100 {
+                offset += 100;
+            } else {
+                return Ok(names);
+            }
+        }
+    }
+
+    pub async fn reserved_slots_load(&self) -> Result<(), Error> {
+        self.send(vec!["reservedSlotsList.load".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    pub async fn reserved_slots_save(&self) -> Result<(), Error> {
+        self.send(vec!["reservedSlotsList.save".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    pub async fn reserved_slots_get_aggressive_join(&self) -> Result<bool, Error> {
+        let mut words = self
+            .send(vec!["reservedSlotsList.aggressiveJoin".to_owned()])
+            .await?
+            .into_iter();
+
+        Ok(next_parse!(words))
+    }
+
+    /// Sets whether non-reserved players get kicked to make room for reserved ones.
+    pub async fn reserved_slots_set_aggressive_join(&self, enabled: bool) -> Result<(), Error> {
+        self.send(vec![
+            "reservedSlotsList.aggressiveJoin".to_owned(),
+            enabled.to_string(),
+        ])
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn spectators_add(&self, name: String) -> Result<(), Error> {
+        self.send(vec!["spectatorList.add".to_owned(), name])
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn spectators_remove(&self, name: String) -> Result<(), Error> {
+        self.send(vec!["spectatorList.remove".to_owned(), name])
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn spectators_clear(&self) -> Result<(), Error> {
+        self.send(vec!["spectatorList.clear".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    /// Returns names of all players on the spectator list.
+    pub async fn spectators_get(&self) -> Result<Vec<String>, Error> {
+        let mut names = Vec::new();
+        let mut offset: usize = 0;
+
+        loop {
+            let words = self
+                .send(vec!["spectatorList.list".to_owned(), offset.to_string()])
+                .await?;
+
+            let num_of_names = words.len();
+            names.extend(words);
+
+            if num_of_names >= 100 {
+                offset += 100;
+            } else {
+                return Ok(names);
+            }
+        }
+    }
+
+    pub async fn spectators_load(&self) -> Result<(), Error> {
+        self.send(vec!["spectatorList.load".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    pub async fn spectators_save(&self) -> Result<(), Error> {
+        self.send(vec!["spectatorList.save".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    pub async fn game_admins_add(&self, admin: GameAdmin) -> Result<(), Error> {
+        self.send(vec![
+            "gameAdmin.add".to_owned(),
+            admin.name,
+            admin.restriction_level.to_string(),
+        ])
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn game_admins_remove(&self, name: String) -> Result<(), Error> {
+        self.send(vec!["gameAdmin.remove".to_owned(), name]).await?;
+
+        Ok(())
+    }
+
+    pub async fn game_admins_clear(&self) -> Result<(), Error> {
+        self.send(vec!["gameAdmin.clear".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    /// Returns all players on the game admin list.
+    pub async fn game_admins_get(&self) -> Result<Vec<GameAdmin>, Error> {
+        let mut words = self
+            .send(vec!["gameAdmin.list".to_owned()])
+            .await?
+            .into_iter()
+            .peekable();
+
+        let mut admins = Vec::new();
+        while words.peek().is_some() {
+            admins.push(GameAdmin {
+                name: next!(words),
+                restriction_level: next_parse!(words),
+            });
+        }
+
+        Ok(admins)
+    }
+
+    pub async fn game_admins_load(&self) -> Result<(), Error> {
+        self.send(vec!["gameAdmin.load".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    pub async fn game_admins_save(&self) -> Result<(), Error> {
+        self.send(vec!["gameAdmin.save".to_owned()]).await?;
+
+        Ok(())
+    }
+
+    /// Returns the current value of a server variable.
+    pub async fn var_get(&self, name: VarName) -> Result<Var, Error> {
+        let words = self.send(vec![name.as_str().to_owned()]).await?;
+        let var = Var::from_words(name, words)?;
+
+        Ok(var)
+    }
+
+    /// Validates and changes the value of a server variable.
+    pub async fn var_set(&self, var: Var) -> Result<(), Error> {
+        var.validate()?;
+
+        for words in var.into_requests() {
+            self.send(words).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Captures all server variables, the map list, reserved slots and game admins.
+    ///
+    /// Variables the server refuses to report are left out.
+    pub async fn config_snapshot(&self) -> Result<ServerConfig, Error> {
+        let mut vars = Vec::with_capacity(VarName::ALL.len());
+        for name in VarName::ALL {
+            match self.var_get(*name).await {
+                Ok(var) => vars.push(var),
+                Err(e) if e.kind() == ErrorKind::Status => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(ServerConfig {
+            vars,
+            maps: self.maps_get().await?,
+            reserved_slots: self.reserved_slots_get().await?,
+            game_admins: self.game_admins_get().await?,
+        })
+    }
+
+    /// Brings the server in line with the given configuration, only sending the settings that
+    /// differ from the live server.
+    ///
+    /// Startup-only variables can't be changed on a running server and are skipped, as is the
+    /// game password while the server is ranked.
+    pub async fn config_apply(&self, config: &ServerConfig) -> Result<(), Error> {
+        for var in &config.vars {
+            let name = var.name();
+            if name.is_startup_only() || self.var_get(name).await? == *var {
+                continue;
+            }
+
+            if let Var::GamePassword(_) = var {
+                let ranked = match self.var_get(VarName::ServerType).await {
+                    Ok(server_type) => server_type == Var::ServerType(ServerType::Ranked),
+                    Err(e) if e.kind() == ErrorKind::Status => false,
+                    Err(e) => return Err(e),
+                };
+                if ranked {
+                    continue;
+                }
+            }
+
+            self.var_set(var.clone()).await?;
+        }
+
+        if self.maps_get().await? != config.maps {
+            self.maps_clear().await?;
+            for (map, gamemode, rounds) in &config.maps {
+                self.maps_add(map.clone(), gamemode.clone(), *rounds, None)
+                    .await?;
+            }
+        }
+
+        let reserved_slots = self.reserved_slots_get().await?;
+        for name in &reserved_slots {
+            if !config.reserved_slots.contains(name) {
+                self.reserved_slots_remove(name.clone()).await?;
+            }
+        }
+        for name in &config.reserved_slots {
+            if !reserved_slots.contains(name) {
+                self.reserved_slots_add(name.clone()).await?;
+            }
+        }
+
+        let game_admins = self.game_admins_get().await?;
+        for admin in &game_admins {
+            if !config.game_admins.contains(admin) {
+                self.game_admins_remove(admin.name.clone()).await?;
+            }
+        }
+        for admin in &config.game_admins {
+            if !game_admins.contains(admin) {
+                self.game_admins_add(admin.clone()).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes the uppercase hex MD5 hash of the hex-decoded salt followed by the password.
+pub(crate) fn hash_password(salt: &str, password: &str) -> Result<String, Error> {
+    let mut input = Vec::with_capacity(salt.len() / 2 + password.len());
+    for pair in salt.as_bytes().chunks(2) {
+        let byte = std::str::from_utf8(pair)
+            .ok()
+            // `from_str_radix` would also accept a sign.
+            .filter(|hex| hex.len() == 2 && hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| Error::new_parse(format!("invalid salt: {}", salt)))?;
+        input.push(byte);
+    }
+    input.extend_from_slice(password.as_bytes());
+
+    Ok(format!("{:X}", md5::compute(input)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_password_known_answer() {
+        assert_eq!(
+            hash_password("5F3A9C0D7E21B4A8F6C3D29E0B1A7E44", "secret").unwrap(),
+            "6D74FD66BA624E507B2749AB4AD77BDF"
+        );
+        // Lowercase hex is accepted, and the password is hashed as UTF-8.
+        assert_eq!(
+            hash_password("00ff", "pässword").unwrap(),
+            "88E9181DDDAB07A6DC6FDF72E8C799E6"
+        );
+        assert_eq!(
+            hash_password("", "secret").unwrap(),
+            "5EBE2294ECD0E0F08EAB7690D2A6EE69"
+        );
+    }
+
+    #[test]
+    fn hash_password_invalid_salt() {
+        for salt in ["ABC", "ZZ", "+1"] {
+            let err = hash_password(salt, "secret").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Parse);
+        }
+    }
 }
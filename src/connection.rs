@@ -1,108 +1,626 @@
-use std::{collections::HashMap, io};
+use std::{collections::HashMap, io, net::SocketAddr, task::Poll, time::Duration};
 
-use bytes::BytesMut;
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{future, SinkExt, StreamExt};
 use tokio::{
-    net::TcpStream,
-    sync::{broadcast, mpsc, oneshot},
+    net::{TcpSocket, TcpStream},
+    sync::{mpsc, oneshot},
+    time,
 };
-use tokio_util::codec::{Decoder, Encoder, Framed};
+use tokio_util::codec::Framed;
 
-use crate::{models::Event, Error, Packet};
+use crate::{
+    client::hash_password,
+    models::Event,
+    protocol::{Codec, Packet, Word, WordDecoding},
+    Error, ErrorKind, EventSender,
+};
 
 pub(crate) type Request = (Vec<String>, oneshot::Sender<Response>);
-pub(crate) type Response = (String, Vec<String>);
+
+#[derive(Debug)]
+pub(crate) enum Message {
+    Request(Request),
+    /// Ends the session once every queued and in-flight request has been answered.
+    Shutdown(oneshot::Sender<()>),
+}
+
+pub(crate) type Response = Result<(String, Vec<String>), Error>;
+
+/// Turns a response into its words, or a status error if it doesn't signify success.
+pub(crate) fn into_result(response: Response) -> Result<Vec<String>, Error> {
+    let (status, words) = response?;
+    if &status == "OK" {
+        Ok(words)
+    } else {
+        Err(Error::new_status(status))
+    }
+}
+
+/// Settings used whenever a connection to the remote host is established.
+#[derive(Clone, Debug)]
+pub(crate) struct Config {
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) tcp_keepalive: bool,
+    pub(crate) tcp_nodelay: bool,
+    pub(crate) max_packet_size: usize,
+    pub(crate) max_in_flight: Option<usize>,
+    pub(crate) heartbeat_interval: Option<Duration>,
+    pub(crate) heartbeat_max_missed: u32,
+    pub(crate) ack_events: bool,
+    pub(crate) word_decoding: WordDecoding,
+    pub(crate) lenient_events: bool,
+    pub(crate) password: Option<String>,
+    pub(crate) events_enable: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            connect_timeout: None,
+            tcp_keepalive: false,
+            tcp_nodelay: false,
+            max_packet_size: Packet::MAX_SIZE,
+            max_in_flight: None,
+            heartbeat_interval: None,
+            heartbeat_max_missed: 3,
+            ack_events: true,
+            word_decoding: WordDecoding::default(),
+            lenient_events: true,
+            password: None,
+            events_enable: false,
+        }
+    }
+}
+
+pub(crate) fn request_channel(bound: Option<usize>) -> (RequestTx, RequestRx) {
+    match bound {
+        Some(bound) => {
+            let (request_tx, request_rx) = mpsc::channel(bound);
+            (
+                RequestTx::Bounded(request_tx),
+                RequestRx::Bounded(request_rx, bound),
+            )
+        }
+        None => {
+            let (request_tx, request_rx) = mpsc::unbounded_channel();
+            (
+                RequestTx::Unbounded(request_tx),
+                RequestRx::Unbounded(request_rx),
+            )
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum RequestTx {
+    Bounded(mpsc::Sender<Message>),
+    Unbounded(mpsc::UnboundedSender<Message>),
+}
+
+impl RequestTx {
+    /// Queues a request, waiting for capacity if the queue is bounded.
+    pub(crate) async fn send(&self, request: Request) -> Result<(), Error> {
+        self.send_message(Message::Request(request)).await
+    }
+
+    pub(crate) async fn send_message(&self, message: Message) -> Result<(), Error> {
+        let result = match self {
+            RequestTx::Bounded(request_tx) => request_tx.send(message).await.map_err(drop),
+            RequestTx::Unbounded(request_tx) => request_tx.send(message).map_err(drop),
+        };
+
+        result.map_err(|_| Error::new_connection_lost())
+    }
+
+    pub(crate) async fn closed(&self) {
+        match self {
+            RequestTx::Bounded(request_tx) => request_tx.closed().await,
+            RequestTx::Unbounded(request_tx) => request_tx.closed().await,
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        match self {
+            RequestTx::Bounded(request_tx) => request_tx.is_closed(),
+            RequestTx::Unbounded(request_tx) => request_tx.is_closed(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum RequestRx {
+    Bounded(mpsc::Receiver<Message>, usize),
+    Unbounded(mpsc::UnboundedReceiver<Message>),
+}
+
+impl RequestRx {
+    pub(crate) async fn recv(&mut self) -> Option<Message> {
+        match self {
+            RequestRx::Bounded(request_rx, _) => request_rx.recv().await,
+            RequestRx::Unbounded(request_rx) => request_rx.recv().await,
+        }
+    }
+
+    /// Stops accepting new messages, while those already queued can still be received.
+    pub(crate) fn close(&mut self) {
+        match self {
+            RequestRx::Bounded(request_rx, _) => request_rx.close(),
+            RequestRx::Unbounded(request_rx) => request_rx.close(),
+        }
+    }
+
+    /// Number of messages that may be queued, or `None` if the queue is unbounded.
+    pub(crate) fn bound(&self) -> Option<usize> {
+        match self {
+            RequestRx::Bounded(_, bound) => Some(*bound),
+            RequestRx::Unbounded(_) => None,
+        }
+    }
+}
 
 pub(crate) struct Connection {
     next_id: u32,
+    max_in_flight: Option<usize>,
+    heartbeat_interval: Option<Duration>,
+    heartbeat_max_missed: u32,
+    ack_events: bool,
+    word_decoding: WordDecoding,
+    lenient_events: bool,
+    server: SocketAddr,
     stream: Framed<TcpStream, Codec>,
-    event_tx: broadcast::Sender<Event>,
-    request_rx: mpsc::UnboundedReceiver<Request>,
+    event_tx: EventSender,
     pending_requests: HashMap<u32, oneshot::Sender<Response>>,
 }
 
 impl Connection {
-    pub(crate) fn new(
-        tcp_stream: TcpStream,
-        event_tx: broadcast::Sender<Event>,
-        request_rx: mpsc::UnboundedReceiver<Request>,
-    ) -> Connection {
-        Connection {
+    /// Connects to the first reachable address and sets up the session described by the config.
+    pub(crate) async fn connect(
+        addrs: &[SocketAddr],
+        config: &Config,
+        event_tx: EventSender,
+    ) -> Result<Connection, Error> {
+        let tcp_stream = match config.connect_timeout {
+            Some(timeout) => match time::timeout(timeout, dial(addrs, config)).await {
+                Ok(tcp_stream) => tcp_stream?,
+                Err(_) => return Err(Error::new_timeout()),
+            },
+            None => dial(addrs, config).await?,
+        };
+        tcp_stream.set_nodelay(config.tcp_nodelay)?;
+        let server = tcp_stream.peer_addr()?;
+
+        let mut connection = Connection {
             next_id: 0,
-            stream: Framed::new(tcp_stream, Codec),
+            max_in_flight: config.max_in_flight,
+            heartbeat_interval: config.heartbeat_interval,
+            heartbeat_max_missed: config.heartbeat_max_missed,
+            ack_events: config.ack_events,
+            word_decoding: config.word_decoding,
+            lenient_events: config.lenient_events,
+            server,
+            stream: Framed::new(tcp_stream, Codec::with_max_size(config.max_packet_size)),
             event_tx,
-            request_rx,
             pending_requests: HashMap::new(),
+        };
+
+        if let Some(ref password) = config.password {
+            let mut words = connection
+                .request(vec!["login.hashed".to_owned()])
+                .await?
+                .into_iter();
+
+            let hash = hash_password(&next!(words), password)?;
+            connection
+                .request(vec!["login.hashed".to_owned(), hash])
+                .await?;
+        }
+
+        if config.events_enable {
+            connection
+                .request(vec!["admin.eventsEnabled".to_owned(), true.to_string()])
+                .await?;
         }
+
+        Ok(connection)
     }
 
-    pub(crate) async fn run(mut self) -> Result<(), Error> {
+    /// Runs until every sender of `request_rx` has been dropped, or the connection is lost.
+    pub(crate) async fn run(mut self, request_rx: &mut RequestRx) -> Result<(), Error> {
+        let mut heartbeat = self.heartbeat_interval.map(|period| {
+            let mut interval = time::interval_at(time::Instant::now() + period, period);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            Heartbeat {
+                interval,
+                response_rx: None,
+                request_id: None,
+                missed: 0,
+            }
+        });
+
         loop {
+            // Requests are left queued while the in-flight limit is reached.
+            let has_capacity = self.has_capacity();
+
             tokio::select! {
                 packet = self.stream.next() => {
                     match packet {
-                        Some(packet) => self.handle_recv(packet?)?,
-                        None => return Ok(()),
+                        Some(packet) => self.handle_recv(packet?).await?,
+                        None => return Err(Error::new_connection_lost()),
                     }
                 }
 
-                request = self.request_rx.recv() => {
-                    match request {
-                        Some((words, response_tx)) => {
-                            let id = self.next_id;
-                            self.next_id = self.next_id.wrapping_add(1);
-                            self.stream
-                                .send(Packet::new(id, false, false, words))
-                                .await?;
-                            self.pending_requests.insert(id, response_tx);
+                // Callers giving up on their requests are forgotten right away, which also frees
+                // up room for queued requests.
+                _ = cancelled(&mut self.pending_requests) => {
+                    self.pending_requests
+                        .retain(|_, response_tx| !response_tx.is_closed());
+                }
+
+                message = request_rx.recv(), if has_capacity => {
+                    match message {
+                        Some(Message::Request(request)) => self.handle_request(request).await?,
+                        Some(Message::Shutdown(done_tx)) => {
+                            return self.shutdown(request_rx, done_tx, heartbeat).await;
                         }
                         None => return Ok(()),
                     }
                 }
+
+                _ = tick(&mut heartbeat) => {
+                    if let Some(ref mut heartbeat) = heartbeat {
+                        self.handle_heartbeat(heartbeat).await?;
+                    }
+                }
             }
         }
     }
 
-    fn handle_recv(&mut self, packet: Packet) -> Result<(), Error> {
-        match (packet.is_response, packet.is_from_server) {
-            (true, false) => {
-                if let Some(response_tx) = self.pending_requests.remove(&packet.id) {
-                    let mut words = packet.words;
-                    if words.is_empty() {
-                        return Err(Error::from(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            "empty response packet",
-                        )));
+    /// Sends a request and waits for its response, handling any packets received meanwhile.
+    pub(crate) async fn request(&mut self, words: Vec<String>) -> Result<Vec<String>, Error> {
+        let (response_tx, mut response_rx) = oneshot::channel();
+        self.handle_request((words, response_tx)).await?;
+
+        loop {
+            match self.stream.next().await {
+                Some(packet) => self.handle_recv(packet?).await?,
+                None => return Err(Error::new_connection_lost()),
+            }
+
+            if let Ok(response) = response_rx.try_recv() {
+                return into_result(response);
+            }
+        }
+    }
+
+    /// Sends the requests still queued, waits for every response, and then ends the session.
+    ///
+    /// The heartbeat keeps running meanwhile, and the session is abandoned as soon as every
+    /// caller of the shutdown has stopped waiting for it.
+    async fn shutdown(
+        mut self,
+        request_rx: &mut RequestRx,
+        done_tx: oneshot::Sender<()>,
+        mut heartbeat: Option<Heartbeat>,
+    ) -> Result<(), Error> {
+        let mut done_txs = vec![done_tx];
+        request_rx.close();
+
+        while let Some(message) = request_rx.recv().await {
+            match message {
+                Message::Request(request) => {
+                    while !self.has_capacity() {
+                        if !self.shutdown_step(&mut heartbeat, &mut done_txs).await? {
+                            return Ok(());
+                        }
                     }
+                    self.handle_request(request).await?;
+                }
+                Message::Shutdown(done_tx) => done_txs.push(done_tx),
+            }
+        }
 
-                    let _ = response_tx.send((words.remove(0), words));
+        loop {
+            self.pending_requests
+                .retain(|_, response_tx| !response_tx.is_closed());
+            // Nobody but the heartbeat waits for its response.
+            let heartbeat_id = heartbeat
+                .as_ref()
+                .and_then(|heartbeat| heartbeat.request_id);
+            if self
+                .pending_requests
+                .keys()
+                .all(|id| Some(*id) == heartbeat_id)
+            {
+                break;
+            }
+
+            if !self.shutdown_step(&mut heartbeat, &mut done_txs).await? {
+                return Ok(());
+            }
+        }
+
+        let (response_tx, mut response_rx) = oneshot::channel();
+        self.handle_request((vec!["quit".to_owned()], response_tx))
+            .await?;
+        loop {
+            if let Ok(response) = response_rx.try_recv() {
+                into_result(response)?;
+                self.stream.close().await?;
+                break;
+            }
+
+            match self.shutdown_step(&mut heartbeat, &mut done_txs).await {
+                Ok(true) => {}
+                Ok(false) => return Ok(()),
+                // The remote host may close the connection before answering.
+                Err(e) if e.kind() == ErrorKind::ConnectionLost => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        for done_tx in done_txs {
+            let _ = done_tx.send(());
+        }
+
+        Ok(())
+    }
+
+    /// Handles the next packet, cancelled request or heartbeat while shutting down, returning
+    /// `false` once every caller of the shutdown has stopped waiting for it.
+    async fn shutdown_step(
+        &mut self,
+        heartbeat: &mut Option<Heartbeat>,
+        done_txs: &mut [oneshot::Sender<()>],
+    ) -> Result<bool, Error> {
+        self.pending_requests
+            .retain(|_, response_tx| !response_tx.is_closed());
+
+        tokio::select! {
+            packet = self.stream.next() => {
+                match packet {
+                    Some(packet) => self.handle_recv(packet?).await?,
+                    None => return Err(Error::new_connection_lost()),
+                }
+            }
+
+            _ = cancelled(&mut self.pending_requests) => {}
+
+            _ = abandoned(done_txs) => return Ok(false),
+
+            _ = tick(heartbeat) => {
+                if let Some(ref mut heartbeat) = heartbeat {
+                    self.handle_heartbeat(heartbeat).await?;
                 }
             }
+        }
+
+        Ok(true)
+    }
+
+    /// Sends the next heartbeat, or declares the connection lost once too many heartbeats in a
+    /// row went unanswered.
+    async fn handle_heartbeat(&mut self, heartbeat: &mut Heartbeat) -> Result<(), Error> {
+        if let Some(ref mut response_rx) = heartbeat.response_rx {
+            if let Err(oneshot::error::TryRecvError::Empty) = response_rx.try_recv() {
+                heartbeat.missed += 1;
+                if heartbeat.missed >= self.heartbeat_max_missed {
+                    return Err(Error::new_connection_lost());
+                }
+
+                return Ok(());
+            }
+        }
+
+        let (response_tx, response_rx) = oneshot::channel();
+        heartbeat.response_rx = Some(response_rx);
+        heartbeat.request_id = Some(self.next_id);
+        heartbeat.missed = 0;
+        self.handle_request((vec!["version".to_owned()], response_tx))
+            .await
+    }
+
+    /// Handles received packets until another request may be sent without exceeding the
+    /// in-flight limit.
+    pub(crate) async fn wait_for_capacity(&mut self) -> Result<(), Error> {
+        while !self.has_capacity() {
+            tokio::select! {
+                packet = self.stream.next() => {
+                    match packet {
+                        Some(packet) => self.handle_recv(packet?).await?,
+                        None => return Err(Error::new_connection_lost()),
+                    }
+                }
+
+                _ = cancelled(&mut self.pending_requests) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn has_capacity(&mut self) -> bool {
+        match self.max_in_flight {
+            Some(max_in_flight) => {
+                self.pending_requests
+                    .retain(|_, response_tx| !response_tx.is_closed());
+                self.pending_requests.len() < max_in_flight
+            }
+            None => true,
+        }
+    }
+
+    pub(crate) async fn handle_request(&mut self, request: Request) -> Result<(), Error> {
+        let (words, response_tx) = request;
+        if response_tx.is_closed() {
+            return Ok(());
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let words = words.into_iter().map(Word::from).collect();
+        self.stream
+            .send(Packet::new(id, false, false, words))
+            .await?;
+        self.pending_requests.insert(id, response_tx);
+        // Forget requests whose caller is no longer waiting for the response.
+        self.pending_requests
+            .retain(|_, response_tx| !response_tx.is_closed());
+
+        Ok(())
+    }
+
+    fn decode(&self, words: Vec<Word>) -> Result<Vec<String>, Error> {
+        words
+            .iter()
+            .map(|word| self.word_decoding.decode(word).map_err(Error::new_parse))
+            .collect()
+    }
+
+    async fn handle_recv(&mut self, packet: Packet) -> Result<(), Error> {
+        match (packet.is_response, packet.is_from_server) {
+            // Responses are matched by id alone, since the origin flag of a response is not
+            // always echoed back faithfully.
+            (true, _) => match self.pending_requests.remove(&packet.id) {
+                Some(response_tx) => {
+                    // A malformed response only fails the request it answers.
+                    let response = if packet.words.is_empty() {
+                        Err(Error::new_protocol("empty response packet"))
+                    } else {
+                        self.decode(packet.words).map(|mut words| {
+                            let status = words.remove(0);
+                            (status, words)
+                        })
+                    };
+                    let _ = response_tx.send(response);
+                }
+                None => self.handle_unexpected(packet),
+            },
             (false, true) => {
-                let _ = self.event_tx.send(Event::from_words(packet.words)?);
+                if self.ack_events {
+                    self.stream
+                        .send(Packet::new(
+                            packet.id,
+                            true,
+                            true,
+                            vec![Word::from_static("OK")],
+                        ))
+                        .await?;
+                }
+
+                // Words are decoded in place, and only copied into the fields of the event.
+                let words: Result<Vec<_>, _> = packet
+                    .words
+                    .iter()
+                    .map(|word| self.word_decoding.decode_str(word))
+                    .collect();
+                let event = match words {
+                    Ok(words) if self.lenient_events => Event::from_words_lenient(&words),
+                    Ok(words) => Event::from_words(&words)?,
+                    // Events that can't be decoded under the strict policy still take their
+                    // place in the sequence.
+                    Err(_) => {
+                        let mut words = decode_lossy(&packet.words).into_iter();
+                        Event::Unknown {
+                            name: words.next().unwrap_or_default(),
+                            words: words.collect(),
+                        }
+                    }
+                };
+                self.event_tx.send(self.server, packet.words, event);
             }
-            _ => unreachable!(),
+            // Requests claiming to originate from the client can't be answered meaningfully.
+            (false, false) => self.handle_unexpected(packet),
         }
 
         Ok(())
     }
+
+    /// Reports a packet that belongs to no exchange to the subscribers of events, rather than
+    /// ending the session.
+    fn handle_unexpected(&self, packet: Packet) {
+        let event = Event::UnexpectedPacket {
+            id: packet.id,
+            is_response: packet.is_response,
+            words: decode_lossy(&packet.words),
+        };
+        self.event_tx.send(self.server, packet.words, event);
+    }
+}
+
+fn decode_lossy(words: &[Word]) -> Vec<String> {
+    words
+        .iter()
+        .map(|word| WordDecoding::Lossy.decode(word).unwrap_or_default())
+        .collect()
 }
 
-struct Codec;
+struct Heartbeat {
+    interval: time::Interval,
+    response_rx: Option<oneshot::Receiver<Response>>,
+    request_id: Option<u32>,
+    missed: u32,
+}
 
-impl Decoder for Codec {
-    type Item = Packet;
-    type Error = io::Error;
+/// Completes once the caller of any pending request is no longer waiting for its response.
+async fn cancelled(pending_requests: &mut HashMap<u32, oneshot::Sender<Response>>) {
+    future::poll_fn(|cx| {
+        for response_tx in pending_requests.values_mut() {
+            if response_tx.poll_closed(cx).is_ready() {
+                return Poll::Ready(());
+            }
+        }
+
+        Poll::Pending
+    })
+    .await
+}
 
-    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        Packet::read(buf)
+/// Completes once every caller of a shutdown is no longer waiting for it to finish.
+async fn abandoned(done_txs: &mut [oneshot::Sender<()>]) {
+    future::poll_fn(|cx| {
+        let mut abandoned = true;
+        for done_tx in done_txs.iter_mut() {
+            abandoned &= done_tx.poll_closed(cx).is_ready();
+        }
+
+        if abandoned {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+async fn tick(heartbeat: &mut Option<Heartbeat>) {
+    match heartbeat {
+        Some(heartbeat) => {
+            heartbeat.interval.tick().await;
+        }
+        None => future::pending().await,
     }
 }
 
-impl Encoder<Packet> for Codec {
-    type Error = io::Error;
+async fn dial(addrs: &[SocketAddr], config: &Config) -> io::Result<TcpStream> {
+    let mut last_err = None;
+
+    for addr in addrs {
+        let socket = match addr {
+            SocketAddr::V4(_) => TcpSocket::new_v4()?,
+            SocketAddr::V6(_) => TcpSocket::new_v6()?,
+        };
+        socket.set_keepalive(config.tcp_keepalive)?;
 
-    fn encode(&mut self, packet: Packet, buf: &mut BytesMut) -> Result<(), Self::Error> {
-        packet.write(buf)
+        match socket.connect(*addr).await {
+            Ok(tcp_stream) => return Ok(tcp_stream),
+            Err(e) => last_err = Some(e),
+        }
     }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any address",
+        )
+    }))
 }
@@ -15,9 +15,12 @@ struct ErrorInner {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ErrorKind {
     ConnectionLost,
+    InvalidValue,
     Io,
     Parse,
+    Protocol,
     Status,
+    Timeout,
 }
 
 impl Error {
@@ -28,7 +31,7 @@ impl Error {
         Error {
             inner: Box::new(ErrorInner {
                 kind,
-                status: status.map(Into::into),
+                status,
                 source: source.map(Into::into),
             }),
         }
@@ -38,6 +41,13 @@ impl Error {
         Error::new(ErrorKind::ConnectionLost, None, None::<Error>)
     }
 
+    pub(crate) fn new_invalid_value<E>(source: E) -> Error
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Error::new(ErrorKind::InvalidValue, None, Some(source))
+    }
+
     pub(crate) fn new_parse<E>(source: E) -> Error
     where
         E: Into<Box<dyn std::error::Error + Send + Sync>>,
@@ -45,10 +55,21 @@ impl Error {
         Error::new(ErrorKind::Parse, None, Some(source))
     }
 
+    pub(crate) fn new_protocol<E>(source: E) -> Error
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Error::new(ErrorKind::Protocol, None, Some(source))
+    }
+
     pub(crate) fn new_status(status: String) -> Error {
         Error::new(ErrorKind::Status, Some(status), None::<Error>)
     }
 
+    pub(crate) fn new_timeout() -> Error {
+        Error::new(ErrorKind::Timeout, None, None::<Error>)
+    }
+
     pub fn kind(&self) -> ErrorKind {
         self.inner.kind
     }
@@ -89,12 +110,15 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.inner.kind {
             ErrorKind::ConnectionLost => f.write_str("connection lost")?,
+            ErrorKind::InvalidValue => f.write_str("invalid value")?,
             ErrorKind::Io => f.write_str("io error")?,
             ErrorKind::Parse => f.write_str("parse error")?,
+            ErrorKind::Protocol => f.write_str("protocol error")?,
             ErrorKind::Status => match self.status() {
                 Some(status) => write!(f, "RCON status error ({})", status)?,
                 None => unreachable!(),
             },
+            ErrorKind::Timeout => f.write_str("request timed out")?,
         };
 
         if let Some(ref source) = self.inner.source {
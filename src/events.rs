@@ -0,0 +1,152 @@
+use std::{
+    fmt,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::SystemTime,
+};
+
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{models::Event, protocol::Word};
+
+/// Event sent by the remote host, together with the circumstances it was received in.
+#[derive(Clone, Debug)]
+pub struct EventEnvelope {
+    /// Time at which the event was received.
+    pub received_at: SystemTime,
+    /// Position of the event among all events received by the client, starting at 0 and
+    /// continuing across reconnects.
+    pub sequence: u64,
+    /// Address of the remote host that sent the event.
+    pub server: SocketAddr,
+    /// Words of the event as received, including its name.
+    pub words: Vec<Word>,
+    pub event: Event,
+}
+
+/// Broadcasts events to every subscriber of a client, numbering them in the order they were
+/// received.
+#[derive(Clone, Debug)]
+pub(crate) struct EventSender {
+    event_tx: broadcast::Sender<EventEnvelope>,
+    next_sequence: Arc<AtomicU64>,
+}
+
+impl EventSender {
+    pub(crate) fn new(capacity: usize) -> EventSender {
+        let (event_tx, _) = broadcast::channel(capacity);
+        EventSender {
+            event_tx,
+            next_sequence: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub(crate) fn send(&self, server: SocketAddr, words: Vec<Word>, event: Event) {
+        let _ = self.event_tx.send(EventEnvelope {
+            received_at: SystemTime::now(),
+            sequence: self.next_sequence.fetch_add(1, Ordering::Relaxed),
+            server,
+            words,
+            event,
+        });
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.event_tx.subscribe()
+    }
+}
+
+/// Item yielded by an [`EventStream`].
+#[derive(Clone, Debug)]
+pub enum Received {
+    Event(EventEnvelope),
+    /// The stream fell behind and the given number of events were dropped before they could be
+    /// received.
+    Lagged(u64),
+}
+
+type Filter = Box<dyn Fn(&Event) -> bool + Send>;
+
+/// Stream of the events sent by the remote host, returned by
+/// [`Client::events`](crate::Client::events).
+///
+/// Lag notifications are yielded regardless of the filters applied to the stream. The stream
+/// ends once the client and every clone of it have been dropped.
+pub struct EventStream {
+    inner: Pin<Box<dyn Stream<Item = Received> + Send>>,
+    filters: Vec<Filter>,
+}
+
+impl EventStream {
+    pub(crate) fn new(event_rx: broadcast::Receiver<EventEnvelope>) -> EventStream {
+        let inner = stream::unfold(event_rx, |mut event_rx| async move {
+            match event_rx.recv().await {
+                Ok(event) => Some((Received::Event(event), event_rx)),
+                Err(RecvError::Lagged(count)) => Some((Received::Lagged(count), event_rx)),
+                Err(RecvError::Closed) => None,
+            }
+        });
+
+        EventStream {
+            inner: Box::pin(inner),
+            filters: Vec::new(),
+        }
+    }
+
+    /// Only yields events for which the predicate returns `true`.
+    pub fn filter<F>(mut self, predicate: F) -> EventStream
+    where
+        F: Fn(&Event) -> bool + Send + 'static,
+    {
+        self.filters.push(Box::new(predicate));
+        self
+    }
+
+    /// Only yields [`Event::PlayerOnKill`] events.
+    pub fn kills(self) -> EventStream {
+        self.filter(|event| matches!(event, Event::PlayerOnKill { .. }))
+    }
+
+    /// Only yields [`Event::PlayerOnChat`] events.
+    pub fn chat(self) -> EventStream {
+        self.filter(|event| matches!(event, Event::PlayerOnChat { .. }))
+    }
+
+    /// Only yields [`Event::PlayerOnChat`] events sent by the given player.
+    pub fn chat_from(self, name: String) -> EventStream {
+        self.filter(
+            move |event| matches!(event, Event::PlayerOnChat { source, .. } if *source == name),
+        )
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Received;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Received>> {
+        loop {
+            match self.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Received::Event(envelope)))
+                    if !self.filters.iter().all(|filter| filter(&envelope.event)) =>
+                {
+                    continue
+                }
+                poll => return poll,
+            }
+        }
+    }
+}
+
+impl fmt::Debug for EventStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventStream")
+            .field("filters", &self.filters.len())
+            .finish()
+    }
+}
@@ -23,16 +23,31 @@ macro_rules! next_parse {
     };
 }
 
+mod builder;
+pub use self::builder::ClientBuilder;
+
 mod client;
 pub use self::client::Client;
 
 mod connection;
-pub(crate) use self::connection::{Connection, Request};
+pub(crate) use self::connection::{Connection, Message, Request};
 
 mod error;
 pub use self::error::{Error, ErrorKind, Result};
 
+mod events;
+pub(crate) use self::events::EventSender;
+pub use self::events::{EventEnvelope, EventStream, Received};
+
 pub mod models;
 
-mod packet;
-pub(crate) use self::packet::Packet;
+pub mod protocol;
+
+mod rate_limit;
+pub use self::rate_limit::RateLimit;
+
+mod reconnect;
+pub use self::reconnect::{ConnectionState, ReconnectPolicy};
+
+#[cfg(feature = "testing")]
+pub mod testing;
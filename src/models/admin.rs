@@ -0,0 +1,11 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Player allowed to use in-game admin commands.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct GameAdmin {
+    pub name: String,
+    /// Restriction level between 0 and 3, where 0 grants access to every admin command.
+    pub restriction_level: u8,
+}
@@ -0,0 +1,150 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct BanEntry {
+    pub id_type: IdType,
+    pub id: String,
+    pub timeout: BanTimeout,
+    pub reason: String,
+}
+
+impl BanEntry {
+    pub(crate) fn from_words<I>(words: &mut I) -> Result<BanEntry, Error>
+    where
+        I: Iterator<Item = String>,
+    {
+        let id_type = next_parse!(words);
+        let id = next!(words);
+        let ban_type = next!(words);
+        let seconds_left = next_parse!(words);
+        let rounds_left = next_parse!(words);
+
+        let timeout = match ban_type.as_ref() {
+            "perm" => BanTimeout::Permanent,
+            "rounds" => BanTimeout::Rounds(rounds_left),
+            "seconds" => BanTimeout::Seconds(seconds_left),
+            other => return Err(Error::new_parse(format!("invalid ban type: {}", other))),
+        };
+
+        Ok(BanEntry {
+            id_type,
+            id,
+            timeout,
+            reason: next!(words),
+        })
+    }
+}
+
+/// Kind of identifier a ban applies to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum IdType {
+    Name,
+    Ip,
+    Guid,
+}
+
+impl IdType {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            IdType::Name => "name",
+            IdType::Ip => "ip",
+            IdType::Guid => "guid",
+        }
+    }
+}
+
+impl std::str::FromStr for IdType {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "name" => Ok(IdType::Name),
+            "ip" => Ok(IdType::Ip),
+            "guid" => Ok(IdType::Guid),
+            other => Err(Error::new_parse(format!("invalid id type: {}", other))),
+        }
+    }
+}
+
+/// Duration of a ban. When listing bans, this holds the remaining time.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum BanTimeout {
+    Permanent,
+    Rounds(u32),
+    Seconds(u64),
+}
+
+impl BanTimeout {
+    pub(crate) fn into_words(self) -> Vec<String> {
+        match self {
+            BanTimeout::Permanent => vec!["perm".to_owned()],
+            BanTimeout::Rounds(rounds) => vec!["rounds".to_owned(), rounds.to_string()],
+            BanTimeout::Seconds(seconds) => vec!["seconds".to_owned(), seconds.to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[&str]) -> std::vec::IntoIter<String> {
+        words
+            .iter()
+            .map(|&word| word.to_owned())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn from_words() {
+        let mut words = words(&[
+            "name", "alice", "perm", "0", "0", "cheating", "guid", "EA_1", "rounds", "0", "3", "",
+            "ip", "10.0.0.1", "seconds", "3600", "0", "spam",
+        ]);
+
+        let ban = BanEntry::from_words(&mut words).unwrap();
+        assert_eq!(ban.id_type, IdType::Name);
+        assert_eq!(ban.id, "alice");
+        assert_eq!(ban.timeout, BanTimeout::Permanent);
+        assert_eq!(ban.reason, "cheating");
+
+        let ban = BanEntry::from_words(&mut words).unwrap();
+        assert_eq!(ban.id_type, IdType::Guid);
+        assert_eq!(ban.timeout, BanTimeout::Rounds(3));
+        assert_eq!(ban.reason, "");
+
+        let ban = BanEntry::from_words(&mut words).unwrap();
+        assert_eq!(ban.id_type, IdType::Ip);
+        assert_eq!(ban.timeout, BanTimeout::Seconds(3600));
+
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn invalid_ban_type() {
+        let mut words = words(&["name", "alice", "forever", "0", "0", ""]);
+
+        assert!(BanEntry::from_words(&mut words).is_err());
+    }
+
+    #[test]
+    fn invalid_id_type() {
+        let mut words = words(&["eaid", "alice", "perm", "0", "0", ""]);
+
+        assert!(BanEntry::from_words(&mut words).is_err());
+    }
+
+    #[test]
+    fn truncated_entry() {
+        let mut words = words(&["name", "alice", "perm", "0", "0"]);
+
+        assert!(BanEntry::from_words(&mut words).is_err());
+    }
+}
@@ -0,0 +1,16 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{GameAdmin, Var};
+
+/// Persistent configuration of a server, as captured by
+/// [`Client::config_snapshot`](crate::Client::config_snapshot).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ServerConfig {
+    pub vars: Vec<Var>,
+    /// Map name, game mode and number of rounds of every map list entry.
+    pub maps: Vec<(String, String, usize)>,
+    pub reserved_slots: Vec<String>,
+    pub game_admins: Vec<GameAdmin>,
+}
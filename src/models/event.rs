@@ -66,55 +66,79 @@ pub enum Event {
     ServerOnMaxPlayerCountChange {
         count: usize,
     },
+    /// Packet sent by the remote host that belongs to no exchange: a request claiming to
+    /// originate from the client, or a response to no pending request, such as a late answer to
+    /// a request whose caller stopped waiting.
+    UnexpectedPacket {
+        id: u32,
+        is_response: bool,
+        words: Vec<String>,
+    },
+    /// Event this crate doesn't know about, a known event that couldn't be parsed while events
+    /// are parsed leniently, or an event whose words couldn't be decoded under
+    /// [`WordDecoding::Strict`](crate::protocol::WordDecoding::Strict), with the words
+    /// following its name.
+    Unknown {
+        name: String,
+        words: Vec<String>,
+    },
 }
 
 impl Event {
-    pub(crate) fn from_words(words: Vec<String>) -> Result<Event, Error> {
-        let mut words = words.into_iter();
+    /// Parses an event, falling back to [`Event::Unknown`] when it is malformed instead of
+    /// failing.
+    pub(crate) fn from_words_lenient<S: AsRef<str>>(words: &[S]) -> Event {
+        Event::from_words(words).unwrap_or_else(|_| {
+            let mut words = words.iter().map(|word| word.as_ref().to_owned());
+            Event::Unknown {
+                name: words.next().unwrap_or_default(),
+                words: words.collect(),
+            }
+        })
+    }
+
+    /// Parses the words of an event, starting with its name.
+    ///
+    /// Only the words kept by the event are copied.
+    pub fn from_words<S: AsRef<str>>(words: &[S]) -> Result<Event, Error> {
+        let mut words = words.iter().map(AsRef::as_ref);
 
-        match next!(words).as_ref() {
+        match next!(words) {
             "player.onJoin" => Ok(Event::PlayerOnJoin {
-                name: next!(words),
-                guid: next!(words),
+                name: next!(words).to_owned(),
+                guid: next!(words).to_owned(),
+            }),
+            "player.onAuthenticated" => Ok(Event::PlayerOnAuthenticated {
+                name: next!(words).to_owned(),
             }),
-            "player.onAuthenticated" => Ok(Event::PlayerOnAuthenticated { name: next!(words) }),
             "player.onDisconnect" => Ok(Event::PlayerOnDisconnect {
-                name: next!(words),
-                reason: next!(words),
+                name: next!(words).to_owned(),
+                reason: next!(words).to_owned(),
             }),
             "player.onLeave" => {
-                next!(words);
-                let offset: usize = next_parse!(words);
-                words.nth(offset);
-
-                Ok(Event::PlayerOnLeave(PlayerInfo {
-                    name: next!(words),
-                    guid: next!(words),
-                    team_id: next_parse!(words),
-                    squad_id: next_parse!(words),
-                    kills: next_parse!(words),
-                    deaths: next_parse!(words),
-                    score: next_parse!(words),
-                    rank: next_parse!(words),
-                    ping: next_parse!(words),
-                    kind: next_parse!(words),
-                }))
+                let name = next!(words);
+                let player = PlayerInfo::from_block(&mut words)?
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| PlayerInfo::new(name.to_owned(), String::new()));
+
+                Ok(Event::PlayerOnLeave(player))
             }
             "player.onSpawn" => Ok(Event::PlayerOnSpawn {
-                name: next!(words),
+                name: next!(words).to_owned(),
                 team_id: next_parse!(words),
             }),
             "player.onKill" => Ok(Event::PlayerOnKill {
-                killer: next!(words),
-                victim: next!(words),
-                weapon: next!(words),
+                killer: next!(words).to_owned(),
+                victim: next!(words).to_owned(),
+                weapon: next!(words).to_owned(),
                 headshot: next_parse!(words),
             }),
             "player.onChat" => {
-                let source = next!(words);
-                let message = next!(words);
+                let source = next!(words).to_owned();
+                let message = next!(words).to_owned();
 
-                let subset = match next!(words).as_ref() {
+                let subset = match next!(words) {
                     "all" => Subset::All,
                     "team" => Subset::Team {
                         team_id: next_parse!(words),
@@ -123,7 +147,9 @@ impl Event {
                         team_id: next_parse!(words),
                         squad_id: next_parse!(words),
                     },
-                    "player" => Subset::Player { name: next!(words) },
+                    "player" => Subset::Player {
+                        name: next!(words).to_owned(),
+                    },
                     other => {
                         return Err(Error::new_parse(format!(
                             "invalid player subset: {}",
@@ -139,45 +165,26 @@ impl Event {
                 })
             }
             "player.onSquadChange" => Ok(Event::PlayerOnSquadChange {
-                name: next!(words),
+                name: next!(words).to_owned(),
                 team_id: next_parse!(words),
                 squad_id: next_parse!(words),
             }),
             "player.onTeamChange" => Ok(Event::PlayerOnTeamChange {
-                name: next!(words),
+                name: next!(words).to_owned(),
                 team_id: next_parse!(words),
                 squad_id: next_parse!(words),
             }),
             "punkBuster.onMessage" => Ok(Event::PunkBusterOnMessage {
-                message: next!(words),
+                message: next!(words).to_owned(),
             }),
-            "server.onRoundOverPlayers" => {
-                let offset: usize = next_parse!(words);
-                words.nth(offset - 1);
-
-                let num_of_players: usize = next_parse!(words);
-                let mut players = Vec::with_capacity(num_of_players);
-                for _ in 0..num_of_players {
-                    players.push(PlayerInfo {
-                        name: next!(words),
-                        guid: next!(words),
-                        team_id: next_parse!(words),
-                        squad_id: next_parse!(words),
-                        kills: next_parse!(words),
-                        deaths: next_parse!(words),
-                        score: next_parse!(words),
-                        rank: next_parse!(words),
-                        ping: next_parse!(words),
-                        kind: next_parse!(words),
-                    })
-                }
-
-                Ok(Event::ServerOnRoundOverPlayers(players))
-            }
+            "server.onRoundOverPlayers" => Ok(Event::ServerOnRoundOverPlayers(
+                PlayerInfo::from_block(&mut words)?,
+            )),
             "server.onRoundOverTeamScores" => {
                 let num_of_teams: usize = next_parse!(words);
 
-                let mut team_scores = Vec::with_capacity(num_of_teams);
+                // The count is sent by the server, so nothing is reserved up front.
+                let mut team_scores = Vec::new();
                 for _ in 0..num_of_teams {
                     let team_score = next_parse!(words);
                     team_scores.push(team_score);
@@ -190,8 +197,8 @@ impl Event {
                 })
             }
             "server.onLevelLoaded" => Ok(Event::ServerOnLevelLoaded {
-                map: next!(words),
-                gamemode: next!(words),
+                map: next!(words).to_owned(),
+                gamemode: next!(words).to_owned(),
                 rounds_played: next_parse!(words),
                 rounds_total: next_parse!(words),
             }),
@@ -201,7 +208,147 @@ impl Event {
             "server.onMaxPlayerCountChange" => Ok(Event::ServerOnMaxPlayerCountChange {
                 count: next_parse!(words),
             }),
-            other => Err(Error::new_parse(format!("invalid event: {}", other))),
+            other => Ok(Event::Unknown {
+                name: other.to_owned(),
+                words: words.map(str::to_owned).collect(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PlayerKind;
+
+    const FIELDS: [&str; 10] = [
+        "name", "guid", "teamId", "squadId", "kills", "deaths", "score", "rank", "ping", "type",
+    ];
+
+    /// Words of an event followed by a player info block with every documented field.
+    fn words(event: &[&str], players: &[[&str; 10]]) -> Vec<String> {
+        let mut words: Vec<String> = event.iter().map(|&word| word.to_owned()).collect();
+        words.push(FIELDS.len().to_string());
+        words.extend(FIELDS.iter().map(|&field| field.to_owned()));
+        words.push(players.len().to_string());
+        for player in players {
+            words.extend(player.iter().map(|&value| value.to_owned()));
+        }
+        words
+    }
+
+    #[test]
+    fn player_on_leave() {
+        let words = words(
+            &["player.onLeave", "alice"],
+            &[[
+                "alice", "EA_1", "1", "2", "10", "3", "1200", "140", "35", "0",
+            ]],
+        );
+
+        match Event::from_words(&words).unwrap() {
+            Event::PlayerOnLeave(player) => {
+                assert_eq!(player.name, "alice");
+                assert_eq!(player.guid, "EA_1");
+                assert_eq!(player.squad_id, 2);
+                assert_eq!(player.score, 1200);
+                assert_eq!(player.rank, 140);
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn player_on_leave_empty_block() {
+        let words = words(&["player.onLeave", "alice"], &[]);
+
+        match Event::from_words(&words).unwrap() {
+            Event::PlayerOnLeave(player) => {
+                assert_eq!(player.name, "alice");
+                assert_eq!(player.guid, "");
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn server_on_round_over_players() {
+        let words = words(
+            &["server.onRoundOverPlayers"],
+            &[
+                [
+                    "alice", "EA_1", "1", "1", "12", "4", "2400", "140", "30", "0",
+                ],
+                ["bob", "EA_2", "2", "0", "0", "0", "900", "20", "45", "2"],
+                ["carl", "", "0", "0", "0", "0", "0", "0", "0", "1"],
+            ],
+        );
+
+        match Event::from_words(&words).unwrap() {
+            Event::ServerOnRoundOverPlayers(players) => {
+                assert_eq!(players.len(), 3);
+                assert_eq!(players[0].kills, 12);
+                assert!(matches!(players[0].kind, PlayerKind::Player));
+                assert_eq!(players[1].name, "bob");
+                assert!(matches!(players[1].kind, PlayerKind::Commander));
+                assert!(matches!(players[2].kind, PlayerKind::Spectator));
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn server_on_round_over_players_without_fields() {
+        let words = ["server.onRoundOverPlayers", "0", "50000000"];
+
+        assert!(Event::from_words(&words).is_err());
+    }
+
+    #[test]
+    fn server_on_round_over_team_scores() {
+        let words = ["server.onRoundOverTeamScores", "2", "120", "87.5", "0"];
+
+        match Event::from_words(&words).unwrap() {
+            Event::ServerOnRoundOverTeamScores {
+                num_of_teams,
+                team_scores,
+                target_score,
+            } => {
+                assert_eq!(num_of_teams, 2);
+                assert_eq!(team_scores, [120.0, 87.5]);
+                assert_eq!(target_score, 0);
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn server_on_round_over_team_scores_huge_count() {
+        for count in ["1099511627776", "18446744073709551615"] {
+            let words = ["server.onRoundOverTeamScores", count, "120", "87.5", "0"];
+
+            assert!(Event::from_words(&words).is_err());
+        }
+    }
+
+    #[test]
+    fn malformed_block_is_unknown() {
+        let words = [
+            "server.onRoundOverPlayers",
+            "2",
+            "name",
+            "type",
+            "1",
+            "alice",
+        ];
+
+        assert!(Event::from_words(&words).is_err());
+        match Event::from_words_lenient(&words) {
+            Event::Unknown { name, words } => {
+                assert_eq!(name, "server.onRoundOverPlayers");
+                assert_eq!(words, ["2", "name", "type", "1", "alice"]);
+            }
+            event => panic!("unexpected event: {:?}", event),
         }
     }
 }
@@ -1,6 +1,15 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+mod admin;
+pub use self::admin::GameAdmin;
+
+mod ban;
+pub use self::ban::{BanEntry, BanTimeout, IdType};
+
+mod config;
+pub use self::config::ServerConfig;
+
 mod event;
 pub use self::event::Event;
 
@@ -10,6 +19,9 @@ pub use self::player::{PlayerInfo, PlayerKind};
 mod server;
 pub use self::server::ServerInfo;
 
+mod vars;
+pub use self::vars::{Faction, Preset, PresetKind, ServerType, UnlockMode, Var, VarName};
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Subset {
@@ -33,6 +33,55 @@ impl PlayerInfo {
             kind: PlayerKind::Player,
         }
     }
+
+    /// Parses a player info block.
+    ///
+    /// Values are matched to the field names heading the block, so fields unknown to this crate
+    /// are skipped and fields missing from the block keep their defaults.
+    pub(crate) fn from_block<'a, I>(words: &mut I) -> Result<Vec<PlayerInfo>, Error>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let num_of_fields: usize = next_parse!(words);
+        let mut fields = Vec::new();
+        for _ in 0..num_of_fields {
+            fields.push(next!(words));
+        }
+
+        let num_of_players: usize = next_parse!(words);
+        // Every player takes up at least one word unless there are no fields, in which case the
+        // count isn't bounded by the length of the block.
+        if fields.is_empty() && num_of_players != 0 {
+            return Err(Error::new_parse(format!(
+                "{} players without fields",
+                num_of_players
+            )));
+        }
+
+        let mut players = Vec::new();
+        for _ in 0..num_of_players {
+            let mut player = PlayerInfo::new(String::new(), String::new());
+            for field in &fields {
+                let value = next!(words);
+                match *field {
+                    "name" => player.name = value.to_owned(),
+                    "guid" => player.guid = value.to_owned(),
+                    "teamId" => player.team_id = value.parse().map_err(Error::new_parse)?,
+                    "squadId" => player.squad_id = value.parse().map_err(Error::new_parse)?,
+                    "kills" => player.kills = value.parse().map_err(Error::new_parse)?,
+                    "deaths" => player.deaths = value.parse().map_err(Error::new_parse)?,
+                    "score" => player.score = value.parse().map_err(Error::new_parse)?,
+                    "rank" => player.rank = value.parse().map_err(Error::new_parse)?,
+                    "ping" => player.ping = value.parse().map_err(Error::new_parse)?,
+                    "type" => player.kind = value.parse()?,
+                    _ => {}
+                }
+            }
+            players.push(player);
+        }
+
+        Ok(players)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -57,3 +106,80 @@ impl std::str::FromStr for PlayerKind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(words: &[&str]) -> Vec<PlayerInfo> {
+        let mut words = words.iter().copied();
+        let players = PlayerInfo::from_block(&mut words).unwrap();
+        assert_eq!(words.next(), None);
+        players
+    }
+
+    #[test]
+    fn reordered_fields() {
+        let players = parse(&[
+            "4", "kills", "name", "teamId", "guid", "1", "7", "alice", "2", "EA_1",
+        ]);
+
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].name, "alice");
+        assert_eq!(players[0].guid, "EA_1");
+        assert_eq!(players[0].team_id, 2);
+        assert_eq!(players[0].kills, 7);
+    }
+
+    #[test]
+    fn unknown_fields() {
+        let players = parse(&[
+            "3", "name", "spm", "ping", "2", "alice", "312.5", "40", "bob", "0", "65535",
+        ]);
+
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0].name, "alice");
+        assert_eq!(players[0].ping, 40);
+        assert_eq!(players[1].name, "bob");
+        assert_eq!(players[1].ping, 65535);
+    }
+
+    #[test]
+    fn commanders() {
+        let players = parse(&[
+            "2", "name", "type", "4", "alice", "0", "bob", "1", "carl", "2", "dave", "3",
+        ]);
+
+        assert!(matches!(players[0].kind, PlayerKind::Player));
+        assert!(matches!(players[1].kind, PlayerKind::Spectator));
+        assert!(matches!(players[2].kind, PlayerKind::Commander));
+        assert!(matches!(players[3].kind, PlayerKind::MobileCommander));
+    }
+
+    #[test]
+    fn invalid_kind() {
+        let mut words = ["2", "name", "type", "1", "alice", "4"].iter().copied();
+
+        assert!(PlayerInfo::from_block(&mut words).is_err());
+    }
+
+    #[test]
+    fn players_without_fields() {
+        assert!(parse(&["0", "0"]).is_empty());
+
+        let mut words = ["0", "50000000"].iter().copied();
+        assert!(PlayerInfo::from_block(&mut words).is_err());
+
+        let mut words = ["0", "18446744073709551615"].iter().copied();
+        assert!(PlayerInfo::from_block(&mut words).is_err());
+    }
+
+    #[test]
+    fn truncated_block() {
+        let mut words = ["2", "name", "kills", "2", "alice", "3", "bob"]
+            .iter()
+            .copied();
+
+        assert!(PlayerInfo::from_block(&mut words).is_err());
+    }
+}
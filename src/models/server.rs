@@ -47,7 +47,8 @@ impl ServerInfo {
         let rounds_total = next_parse!(words);
         let num_of_teams: usize = next_parse!(words);
 
-        let mut team_scores = Vec::with_capacity(num_of_teams);
+        // The count is sent by the server, so nothing is reserved up front.
+        let mut team_scores = Vec::new();
         for _ in 0..num_of_teams {
             let team_score = next_parse!(words);
             team_scores.push(team_score);
@@ -81,3 +82,58 @@ impl ServerInfo {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(num_of_teams: &str) -> Vec<String> {
+        [
+            "Server",
+            "12",
+            "64",
+            "ConquestLarge0",
+            "MP_Prison",
+            "0",
+            "2",
+            num_of_teams,
+            "120",
+            "87.5",
+            "0",
+            "",
+            "true",
+            "true",
+            "false",
+            "3600",
+            "600",
+            "127.0.0.1:25200",
+            "",
+            "false",
+            "EU",
+            "i3d-ams",
+            "NL",
+            "12",
+            "IN_GAME",
+        ]
+        .iter()
+        .map(|&word| word.to_owned())
+        .collect()
+    }
+
+    #[test]
+    fn from_words() {
+        let info = ServerInfo::from_words(words("2")).unwrap();
+
+        assert_eq!(info.map, "MP_Prison");
+        assert_eq!(info.team_scores, [120.0, 87.5]);
+        assert_eq!(info.addr, "127.0.0.1:25200".parse().unwrap());
+        assert_eq!(info.blaze_game_state, "IN_GAME");
+    }
+
+    #[test]
+    fn huge_team_count() {
+        for count in ["1099511627776", "18446744073709551615"] {
+            assert!(ServerInfo::from_words(words(count)).is_err());
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use std::vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+macro_rules! vars {
+    ($($(#[$meta:meta])* $variant:ident($ty:ty) => $name:literal,)*) => {
+        /// Name of a server variable.
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+        pub enum VarName {
+            $($(#[$meta])* $variant,)*
+        }
+
+        impl VarName {
+            /// Every known server variable.
+            pub const ALL: &'static [VarName] = &[$(VarName::$variant,)*];
+
+            /// Returns the name of the command used to query or change this variable.
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(VarName::$variant => $name,)*
+                }
+            }
+        }
+
+        impl std::str::FromStr for VarName {
+            type Err = Error;
+
+            fn from_str(input: &str) -> Result<Self, Self::Err> {
+                match input {
+                    $($name => Ok(VarName::$variant),)*
+                    other => Err(Error::new_parse(format!("invalid var name: {}", other))),
+                }
+            }
+        }
+
+        /// Server variable together with its value.
+        #[derive(Clone, Debug, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+        pub enum Var {
+            $($(#[$meta])* $variant($ty),)*
+        }
+
+        impl Var {
+            pub fn name(&self) -> VarName {
+                match self {
+                    $(Var::$variant(_) => VarName::$variant,)*
+                }
+            }
+
+            pub(crate) fn from_words(name: VarName, words: Vec<String>) -> Result<Var, Error> {
+                let mut words = words.into_iter();
+
+                match name {
+                    $(VarName::$variant => Ok(Var::$variant(Value::parse(&mut words)?)),)*
+                }
+            }
+
+            pub(crate) fn into_requests(self) -> Vec<Vec<String>> {
+                match self {
+                    $(Var::$variant(value) => value.into_requests($name),)*
+                }
+            }
+        }
+    };
+}
+
+vars! {
+    /// Whether spotted targets are visible in the 3d-world.
+    ThreeDSpotting(bool) => "vars.3dSpotting",
+    /// Whether players may switch to third-person vehicle cameras.
+    ThirdPersonCam(bool) => "vars.3pCam",
+    /// Whether spectators may join without being on the spectator list. Startup only.
+    AlwaysAllowSpectators(bool) => "vars.alwaysAllowSpectators",
+    AutoBalance(bool) => "vars.autoBalance",
+    SkillBasedBalance(bool) => "vars.SkillBasedBalance",
+    /// Bullet damage scale factor, in percent.
+    BulletDamage(u32) => "vars.bulletDamage",
+    Commander(bool) => "vars.commander",
+    CrossHair(bool) => "vars.crossHair",
+    ForceReloadWholeMags(bool) => "vars.forceReloadWholeMags",
+    FriendlyFire(bool) => "vars.friendlyFire",
+    /// Scale factor for number of tickets to end round, in percent.
+    GameModeCounter(u32) => "vars.gameModeCounter",
+    /// Game password, empty if none. Cannot be set on ranked servers.
+    GamePassword(String) => "vars.gamePassword",
+    /// Index of the weapon playlist used in Gun Master, between 0 and 4.
+    GunMasterWeaponsPreset(u8) => "vars.gunMasterWeaponsPreset",
+    HitIndicatorsEnabled(bool) => "vars.hitIndicatorsEnabled",
+    Hud(bool) => "vars.hud",
+    /// Number of rounds an idle-kicked player gets banned for, 0 disables the ban.
+    IdleBanRounds(u32) => "vars.idleBanRounds",
+    /// Seconds a player can be idle before getting kicked, 0 disables the kick.
+    IdleTimeout(u32) => "vars.idleTimeout",
+    /// Whether only players with rank 10 or lower can join.
+    IsNoobOnlyJoin(bool) => "vars.IsNoobOnlyJoin",
+    KillCam(bool) => "vars.killCam",
+    /// Desired maximum number of players, between 1 and 64.
+    MaxPlayers(u32) => "vars.maxPlayers",
+    MaxSpectators(u32) => "vars.maxSpectators",
+    MiniMap(bool) => "vars.miniMap",
+    MiniMapSpotting(bool) => "vars.miniMapSpotting",
+    /// MP experience of the server, such as `CQCLASSIC`. Startup only.
+    MpExperience(String) => "vars.mpExperience",
+    NameTag(bool) => "vars.nameTag",
+    OnlySquadLeaderSpawn(bool) => "vars.onlySquadLeaderSpawn",
+    /// Player respawn time scale factor, in percent.
+    PlayerRespawnTime(u32) => "vars.playerRespawnTime",
+    /// Server preset. Startup only.
+    Preset(Preset) => "vars.preset",
+    RegenerateHealth(bool) => "vars.regenerateHealth",
+    /// Duration of pre-round, in seconds.
+    RoundLockdownCountdown(u32) => "vars.roundLockdownCountdown",
+    /// Seconds after the first player is ready until the ready screen gets bypassed.
+    RoundPlayersReadyBypassTimer(u32) => "vars.roundPlayersReadyBypassTimer",
+    /// Players per team that need to be ready before the round can start.
+    RoundPlayersReadyMinCount(u32) => "vars.roundPlayersReadyMinCount",
+    /// Percentage of players that need to be ready before the round can start.
+    RoundPlayersReadyPercent(u32) => "vars.roundPlayersReadyPercent",
+    /// Minimum number of players for the round to restart in pre-round.
+    RoundRestartPlayerCount(i32) => "vars.roundRestartPlayerCount",
+    /// Minimum number of players required to begin a round.
+    RoundStartPlayerCount(i32) => "vars.roundStartPlayerCount",
+    /// Percentage of the default time limit, 0 disables the time limit.
+    RoundTimeLimit(u32) => "vars.roundTimeLimit",
+    /// Seconds to transition into game round after player requirement has been met.
+    RoundWarmupTimeout(u32) => "vars.roundWarmupTimeout",
+    /// Description displayed on Battlelog, shorter than 256 characters.
+    ServerDescription(String) => "vars.serverDescription",
+    /// Welcome message yelled to each player on first deploy, shorter than 256 characters.
+    ServerMessage(String) => "vars.serverMessage",
+    ServerName(String) => "vars.serverName",
+    /// Server type. Startup only.
+    ServerType(ServerType) => "vars.serverType",
+    /// Soldier max health scale factor, in percent.
+    SoldierHealth(u32) => "vars.soldierHealth",
+    /// Faction of teams 1 to 4.
+    TeamFactionOverride([Faction; 4]) => "vars.teamFactionOverride",
+    /// Number of teamkills allowed during one round, 0 disables kill counting.
+    TeamKillCountForKick(u32) => "vars.teamKillCountForKick",
+    /// Number of teamkill kicks that lead to a permanent ban, 0 disables the ban.
+    TeamKillKickForBan(u32) => "vars.teamKillKickForBan",
+    TeamKillValueDecreasePerSecond(f32) => "vars.teamKillValueDecreasePerSecond",
+    /// Highest kill-value allowed before a player gets kicked, 0 disables the kick.
+    TeamKillValueForKick(f32) => "vars.teamKillValueForKick",
+    TeamKillValueIncrease(f32) => "vars.teamKillValueIncrease",
+    /// Ticket bleed rate, in percent.
+    TicketBleedRate(u32) => "vars.ticketBleedRate",
+    UnlockMode(UnlockMode) => "vars.unlockMode",
+    VehicleSpawnAllowed(bool) => "vars.vehicleSpawnAllowed",
+    /// Vehicle spawn delay scale factor, in percent.
+    VehicleSpawnDelay(u32) => "vars.vehicleSpawnDelay",
+}
+
+impl VarName {
+    /// Checks if the variable can only be changed during server startup.
+    pub fn is_startup_only(self) -> bool {
+        matches!(
+            self,
+            VarName::AlwaysAllowSpectators
+                | VarName::MpExperience
+                | VarName::Preset
+                | VarName::ServerType
+        )
+    }
+}
+
+impl Var {
+    /// Checks the value against the limits documented by the protocol.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            Var::GamePassword(password)
+                if password.len() > 16 || !password.chars().all(|c| c.is_ascii_alphanumeric()) =>
+            {
+                Err(Error::new_invalid_value(
+                    "password must be at most 16 alphanumeric characters",
+                ))
+            }
+            Var::GunMasterWeaponsPreset(index) if *index > 4 => Err(Error::new_invalid_value(
+                format!("invalid gun master weapons preset: {}", index),
+            )),
+            Var::MaxPlayers(count) if !(1..=64).contains(count) => Err(Error::new_invalid_value(
+                format!("invalid max players: {}", count),
+            )),
+            Var::RoundPlayersReadyPercent(percent) if *percent > 100 => Err(
+                Error::new_invalid_value(format!("invalid percentage: {}", percent)),
+            ),
+            Var::ServerDescription(message) | Var::ServerMessage(message)
+                if message.chars().count() >= 256 =>
+            {
+                Err(Error::new_invalid_value(
+                    "message must be shorter than 256 characters",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Preset {
+    pub kind: PresetKind,
+    /// Whether the preset overrides conflicting settings until set back to custom.
+    pub locked: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum PresetKind {
+    Normal,
+    Hardcore,
+    Infantry,
+    Classic,
+    Custom,
+}
+
+impl PresetKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            PresetKind::Normal => "NORMAL",
+            PresetKind::Hardcore => "HARDCORE",
+            PresetKind::Infantry => "INFANTRY",
+            PresetKind::Classic => "CLASSIC",
+            PresetKind::Custom => "CUSTOM",
+        }
+    }
+}
+
+impl std::str::FromStr for PresetKind {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_uppercase().as_ref() {
+            "NORMAL" => Ok(PresetKind::Normal),
+            "HARDCORE" => Ok(PresetKind::Hardcore),
+            "INFANTRY" => Ok(PresetKind::Infantry),
+            "CLASSIC" => Ok(PresetKind::Classic),
+            "CUSTOM" => Ok(PresetKind::Custom),
+            _ => Err(Error::new_parse(format!("invalid preset: {}", input))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ServerType {
+    Official,
+    Ranked,
+    Unranked,
+    Private,
+}
+
+impl ServerType {
+    fn as_str(self) -> &'static str {
+        match self {
+            ServerType::Official => "Official",
+            ServerType::Ranked => "Ranked",
+            ServerType::Unranked => "Unranked",
+            ServerType::Private => "Private",
+        }
+    }
+}
+
+impl std::str::FromStr for ServerType {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_ref() {
+            "official" => Ok(ServerType::Official),
+            "ranked" => Ok(ServerType::Ranked),
+            "unranked" => Ok(ServerType::Unranked),
+            "private" => Ok(ServerType::Private),
+            _ => Err(Error::new_parse(format!("invalid server type: {}", input))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum UnlockMode {
+    All,
+    Common,
+    None,
+    Stats,
+}
+
+impl UnlockMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            UnlockMode::All => "all",
+            UnlockMode::Common => "common",
+            UnlockMode::None => "none",
+            UnlockMode::Stats => "stats",
+        }
+    }
+}
+
+impl std::str::FromStr for UnlockMode {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_ref() {
+            "all" => Ok(UnlockMode::All),
+            "common" => Ok(UnlockMode::Common),
+            "none" => Ok(UnlockMode::None),
+            "stats" => Ok(UnlockMode::Stats),
+            _ => Err(Error::new_parse(format!("invalid unlock mode: {}", input))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Faction {
+    Us,
+    Ru,
+    Cn,
+}
+
+impl Faction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Faction::Us => "0",
+            Faction::Ru => "1",
+            Faction::Cn => "2",
+        }
+    }
+}
+
+impl std::str::FromStr for Faction {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "0" => Ok(Faction::Us),
+            "1" => Ok(Faction::Ru),
+            "2" => Ok(Faction::Cn),
+            other => Err(Error::new_parse(format!("invalid faction: {}", other))),
+        }
+    }
+}
+
+trait Value: Sized {
+    fn parse(words: &mut vec::IntoIter<String>) -> Result<Self, Error>;
+
+    fn into_requests(self, name: &'static str) -> Vec<Vec<String>>;
+}
+
+macro_rules! impl_value {
+    ($($ty:ty),*) => {
+        $(
+            impl Value for $ty {
+                fn parse(words: &mut vec::IntoIter<String>) -> Result<Self, Error> {
+                    Ok(next_parse!(words))
+                }
+
+                fn into_requests(self, name: &'static str) -> Vec<Vec<String>> {
+                    vec![vec![name.to_owned(), self.to_string()]]
+                }
+            }
+        )*
+    };
+}
+
+impl_value!(bool, u8, u32, i32, f32);
+
+impl Value for String {
+    fn parse(words: &mut vec::IntoIter<String>) -> Result<Self, Error> {
+        Ok(next!(words))
+    }
+
+    fn into_requests(self, name: &'static str) -> Vec<Vec<String>> {
+        vec![vec![name.to_owned(), self]]
+    }
+}
+
+impl Value for Preset {
+    fn parse(words: &mut vec::IntoIter<String>) -> Result<Self, Error> {
+        Ok(Preset {
+            kind: next_parse!(words),
+            locked: next_parse!(words),
+        })
+    }
+
+    fn into_requests(self, name: &'static str) -> Vec<Vec<String>> {
+        vec![vec![
+            name.to_owned(),
+            self.kind.as_str().to_owned(),
+            self.locked.to_string(),
+        ]]
+    }
+}
+
+impl Value for ServerType {
+    fn parse(words: &mut vec::IntoIter<String>) -> Result<Self, Error> {
+        Ok(next_parse!(words))
+    }
+
+    fn into_requests(self, name: &'static str) -> Vec<Vec<String>> {
+        vec![vec![name.to_owned(), self.as_str().to_owned()]]
+    }
+}
+
+impl Value for UnlockMode {
+    fn parse(words: &mut vec::IntoIter<String>) -> Result<Self, Error> {
+        Ok(next_parse!(words))
+    }
+
+    fn into_requests(self, name: &'static str) -> Vec<Vec<String>> {
+        vec![vec![name.to_owned(), self.as_str().to_owned()]]
+    }
+}
+
+impl Value for [Faction; 4] {
+    fn parse(words: &mut vec::IntoIter<String>) -> Result<Self, Error> {
+        Ok([
+            next_parse!(words),
+            next_parse!(words),
+            next_parse!(words),
+            next_parse!(words),
+        ])
+    }
+
+    fn into_requests(self, name: &'static str) -> Vec<Vec<String>> {
+        self.iter()
+            .enumerate()
+            .map(|(index, faction)| {
+                vec![
+                    name.to_owned(),
+                    (index + 1).to_string(),
+                    faction.as_str().to_owned(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|&word| word.to_owned()).collect()
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for &name in VarName::ALL {
+            assert_eq!(name.as_str().parse::<VarName>().unwrap(), name);
+        }
+    }
+
+    #[test]
+    fn scalar() {
+        let var = Var::from_words(VarName::MaxPlayers, words(&["64"])).unwrap();
+        assert_eq!(var, Var::MaxPlayers(64));
+        assert_eq!(var.into_requests(), [["vars.maxPlayers", "64"]]);
+
+        let var = Var::from_words(VarName::FriendlyFire, words(&["true"])).unwrap();
+        assert_eq!(var.into_requests(), [["vars.friendlyFire", "true"]]);
+
+        assert!(Var::from_words(VarName::MaxPlayers, words(&["many"])).is_err());
+        assert!(Var::from_words(VarName::MaxPlayers, words(&[])).is_err());
+    }
+
+    #[test]
+    fn team_faction_override() {
+        let var =
+            Var::from_words(VarName::TeamFactionOverride, words(&["0", "1", "2", "0"])).unwrap();
+        assert_eq!(
+            var,
+            Var::TeamFactionOverride([Faction::Us, Faction::Ru, Faction::Cn, Faction::Us])
+        );
+
+        // The value is queried at once, but set one team at a time.
+        assert_eq!(
+            var.into_requests(),
+            [
+                ["vars.teamFactionOverride", "1", "0"],
+                ["vars.teamFactionOverride", "2", "1"],
+                ["vars.teamFactionOverride", "3", "2"],
+                ["vars.teamFactionOverride", "4", "0"],
+            ]
+        );
+
+        assert!(Var::from_words(VarName::TeamFactionOverride, words(&["0", "1", "2"])).is_err());
+        assert!(
+            Var::from_words(VarName::TeamFactionOverride, words(&["0", "1", "2", "3"])).is_err()
+        );
+    }
+
+    #[test]
+    fn preset() {
+        let var = Var::from_words(VarName::Preset, words(&["hardcore", "true"])).unwrap();
+        assert_eq!(
+            var,
+            Var::Preset(Preset {
+                kind: PresetKind::Hardcore,
+                locked: true,
+            })
+        );
+        assert_eq!(var.into_requests(), [["vars.preset", "HARDCORE", "true"]]);
+
+        assert!(Var::from_words(VarName::Preset, words(&["NORMAL"])).is_err());
+        assert!(Var::from_words(VarName::Preset, words(&["EXTREME", "false"])).is_err());
+    }
+
+    fn is_valid(var: Var) -> bool {
+        match var.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                assert_eq!(e.kind(), ErrorKind::InvalidValue);
+                false
+            }
+        }
+    }
+
+    #[test]
+    fn validate_game_password() {
+        assert!(is_valid(Var::GamePassword(String::new())));
+        assert!(is_valid(Var::GamePassword("a".repeat(16))));
+        assert!(!is_valid(Var::GamePassword("a".repeat(17))));
+        assert!(!is_valid(Var::GamePassword("pass word".to_owned())));
+        assert!(!is_valid(Var::GamePassword("pässword".to_owned())));
+    }
+
+    #[test]
+    fn validate_bounds() {
+        assert!(is_valid(Var::GunMasterWeaponsPreset(4)));
+        assert!(!is_valid(Var::GunMasterWeaponsPreset(5)));
+
+        assert!(!is_valid(Var::MaxPlayers(0)));
+        assert!(is_valid(Var::MaxPlayers(1)));
+        assert!(is_valid(Var::MaxPlayers(64)));
+        assert!(!is_valid(Var::MaxPlayers(65)));
+
+        assert!(is_valid(Var::RoundPlayersReadyPercent(100)));
+        assert!(!is_valid(Var::RoundPlayersReadyPercent(101)));
+
+        assert!(is_valid(Var::ServerMessage("é".repeat(255))));
+        assert!(!is_valid(Var::ServerMessage("a".repeat(256))));
+        assert!(!is_valid(Var::ServerDescription("a".repeat(256))));
+
+        assert!(is_valid(Var::BulletDamage(1000)));
+    }
+}
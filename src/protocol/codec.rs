@@ -0,0 +1,53 @@
+use std::io;
+
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::Packet;
+
+/// Frames a byte stream into [`Packet`]s and back.
+///
+/// Decoding fails with [`io::ErrorKind::InvalidData`] if a packet declares a size or word count
+/// beyond the limits, before any of its body is buffered.
+#[derive(Clone, Debug)]
+pub struct Codec {
+    max_size: usize,
+}
+
+impl Codec {
+    pub fn new() -> Codec {
+        Codec::with_max_size(Packet::MAX_SIZE)
+    }
+
+    /// Creates a codec refusing to encode or decode packets larger than `max_size` bytes.
+    pub fn with_max_size(max_size: usize) -> Codec {
+        Codec { max_size }
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Codec {
+        Codec::new()
+    }
+}
+
+impl Decoder for Codec {
+    type Item = Packet;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        Packet::read(buf, self.max_size)
+    }
+}
+
+impl Encoder<Packet> for Codec {
+    type Error = io::Error;
+
+    fn encode(&mut self, packet: Packet, buf: &mut BytesMut) -> Result<(), Self::Error> {
+        packet.write(buf, self.max_size)
+    }
+}
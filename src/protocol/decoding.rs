@@ -0,0 +1,85 @@
+use std::{borrow::Cow, str};
+
+use super::Word;
+
+/// How words that aren't valid UTF-8 are turned into strings.
+///
+/// Some servers send player names and PunkBuster messages encoded as Latin-1.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WordDecoding {
+    /// Fails the response containing the word, while events containing it are delivered as
+    /// [`Event::Unknown`](crate::models::Event::Unknown).
+    Strict,
+    /// Replaces invalid sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    Lossy,
+    /// Decodes the word as Latin-1, which never fails nor loses information.
+    #[default]
+    Latin1,
+}
+
+impl WordDecoding {
+    pub fn decode(self, word: &Word) -> Result<String, str::Utf8Error> {
+        self.decode_str(word).map(Cow::into_owned)
+    }
+
+    /// Decodes the word, borrowing it instead of copying if it is valid UTF-8.
+    pub fn decode_str(self, word: &Word) -> Result<Cow<'_, str>, str::Utf8Error> {
+        match word.to_str() {
+            Ok(word) => Ok(Cow::Borrowed(word)),
+            Err(e) => match self {
+                WordDecoding::Strict => Err(e),
+                WordDecoding::Lossy => Ok(String::from_utf8_lossy(word.as_bytes())),
+                WordDecoding::Latin1 => Ok(Cow::Owned(
+                    word.as_bytes().iter().map(|&b| char::from(b)).collect(),
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latin1_word() -> Word {
+        Word::from(b"b\xe9b".to_vec())
+    }
+
+    #[test]
+    fn valid_utf8_is_borrowed() {
+        let word = Word::from_static("béb");
+
+        for decoding in [
+            WordDecoding::Strict,
+            WordDecoding::Lossy,
+            WordDecoding::Latin1,
+        ] {
+            assert!(matches!(
+                decoding.decode_str(&word),
+                Ok(Cow::Borrowed("béb"))
+            ));
+        }
+    }
+
+    #[test]
+    fn strict() {
+        assert!(WordDecoding::Strict.decode(&latin1_word()).is_err());
+    }
+
+    #[test]
+    fn lossy() {
+        assert_eq!(
+            WordDecoding::Lossy.decode(&latin1_word()).unwrap(),
+            "b\u{fffd}b"
+        );
+    }
+
+    #[test]
+    fn latin1() {
+        assert_eq!(WordDecoding::Latin1.decode(&latin1_word()).unwrap(), "béb");
+
+        let word = Word::from((0..=255).collect::<Vec<u8>>());
+        let decoded = WordDecoding::Latin1.decode(&word).unwrap();
+        assert!(decoded.chars().map(u32::from).eq(0..=255));
+    }
+}
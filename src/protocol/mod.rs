@@ -0,0 +1,13 @@
+//! Framing of the RCON protocol, for use outside of [`Client`](crate::Client).
+
+mod codec;
+pub use self::codec::Codec;
+
+mod decoding;
+pub use self::decoding::WordDecoding;
+
+mod packet;
+pub use self::packet::Packet;
+
+mod word;
+pub use self::word::Word;
@@ -0,0 +1,253 @@
+use std::{convert::TryInto, io, mem};
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use super::Word;
+
+/// A single request or response, made up of a sequence number and words.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Packet {
+    /// Sequence id, of which only the lower 30 bits are transmitted.
+    pub id: u32,
+    pub is_response: bool,
+    pub is_from_server: bool,
+    pub words: Vec<Word>,
+}
+
+impl Packet {
+    /// Size in bytes of the sequence, size and word count fields.
+    pub const HEADER_SIZE: usize = 3 * mem::size_of::<u32>();
+    const WORD_HEADER_FOOTER_SIZE: usize = mem::size_of::<u32>() + mem::size_of::<u8>();
+    /// Largest packet in bytes the protocol allows.
+    pub const MAX_SIZE: usize = 16384;
+
+    /// Bits of the sequence carrying the id.
+    pub const ID_MASK: u32 = 0x3FFF_FFFF;
+    /// Bit of the sequence set if the packet is a response.
+    pub const IS_RESPONSE: u32 = 0x4000_0000;
+    /// Bit of the sequence set if the packet belongs to a request originating from the server.
+    pub const IS_FROM_SERVER: u32 = 0x8000_0000;
+
+    pub fn new(id: u32, is_response: bool, is_from_server: bool, words: Vec<Word>) -> Packet {
+        Packet {
+            id,
+            is_response,
+            is_from_server,
+            words,
+        }
+    }
+
+    /// Splits a sequence into its id and the response and origin flags.
+    pub fn from_sequence(sequence: u32, words: Vec<Word>) -> Packet {
+        Packet::new(
+            sequence & Packet::ID_MASK,
+            sequence & Packet::IS_RESPONSE != 0,
+            sequence & Packet::IS_FROM_SERVER != 0,
+            words,
+        )
+    }
+
+    /// Combines the id and the response and origin flags into a sequence.
+    pub fn sequence(&self) -> u32 {
+        let mut sequence = self.id & Packet::ID_MASK;
+        if self.is_response {
+            sequence |= Packet::IS_RESPONSE;
+        }
+        if self.is_from_server {
+            sequence |= Packet::IS_FROM_SERVER;
+        }
+
+        sequence
+    }
+
+    /// Returns the size in bytes of the encoded packet.
+    pub fn size(&self) -> usize {
+        Packet::HEADER_SIZE
+            + self
+                .words
+                .iter()
+                .map(|w| Packet::WORD_HEADER_FOOTER_SIZE + w.len())
+                .sum::<usize>()
+    }
+
+    pub(crate) fn read(buf: &mut BytesMut, max_size: usize) -> io::Result<Option<Packet>> {
+        if buf.len() < Packet::HEADER_SIZE {
+            return Ok(None);
+        }
+        let mut size_slice = [0_u8; 4];
+        size_slice.copy_from_slice(&buf[4..8]);
+        let size: usize = u32::from_le_bytes(size_slice).try_into().unwrap();
+        if size < Packet::HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "packet size smaller than header",
+            ));
+        }
+        if size > max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "max packet size exceeded",
+            ));
+        }
+        let mut word_count_slice = [0_u8; 4];
+        word_count_slice.copy_from_slice(&buf[8..12]);
+        let word_count: usize = u32::from_le_bytes(word_count_slice).try_into().unwrap();
+        // Every word takes up at least its size field and terminator.
+        if word_count > (size - Packet::HEADER_SIZE) / Packet::WORD_HEADER_FOOTER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "word count exceeds packet size",
+            ));
+        }
+        if buf.len() < size {
+            buf.reserve(size - buf.len());
+            return Ok(None);
+        }
+        let sequence = buf.split_to(Packet::HEADER_SIZE).get_u32_le();
+        // The body is frozen once, so that every word is a cheap slice of the same buffer.
+        let mut body_buf = buf.split_to(size - Packet::HEADER_SIZE).freeze();
+        let mut words = Vec::with_capacity(word_count);
+
+        for _ in 0..word_count {
+            if body_buf.len() < mem::size_of::<u32>() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "malformed packet",
+                ));
+            }
+            let word_size: usize = body_buf.get_u32_le().try_into().unwrap();
+            if body_buf.len() < word_size + mem::size_of::<u8>() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "malformed packet",
+                ));
+            }
+            words.push(Word::from(body_buf.split_to(word_size)));
+            if body_buf.get_u8() != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "malformed packet",
+                ));
+            }
+        }
+
+        Ok(Some(Packet::from_sequence(sequence, words)))
+    }
+
+    pub(crate) fn write(&self, buf: &mut BytesMut, max_size: usize) -> io::Result<()> {
+        let size = self.size();
+        if size > max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "max packet size exceeded",
+            ));
+        }
+        buf.reserve(size);
+        buf.put_u32_le(self.sequence());
+        buf.put_u32_le(size as u32);
+        buf.put_u32_le(self.words.len() as u32);
+
+        for w in &self.words {
+            buf.put_u32_le(w.len() as u32);
+            buf.put(w.as_bytes());
+            buf.put_u8(0);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(sequence: u32, size: u32, word_count: u32) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u32_le(sequence);
+        buf.put_u32_le(size);
+        buf.put_u32_le(word_count);
+        buf
+    }
+
+    fn encode(packet: &Packet) -> BytesMut {
+        let mut buf = BytesMut::new();
+        packet.write(&mut buf, Packet::MAX_SIZE).unwrap();
+        buf
+    }
+
+    #[test]
+    fn round_trip() {
+        let packet = Packet::new(
+            7,
+            true,
+            false,
+            vec![Word::from_static("OK"), Word::from_static("")],
+        );
+        let mut buf = encode(&packet);
+
+        assert_eq!(
+            Packet::read(&mut buf, Packet::MAX_SIZE).unwrap(),
+            Some(packet)
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn partial_header() {
+        let mut buf = header(0, 12, 0);
+        buf.truncate(Packet::HEADER_SIZE - 1);
+
+        assert_eq!(Packet::read(&mut buf, Packet::MAX_SIZE).unwrap(), None);
+        assert_eq!(buf.len(), Packet::HEADER_SIZE - 1);
+    }
+
+    #[test]
+    fn partial_body() {
+        let mut buf = encode(&Packet::new(
+            0,
+            false,
+            false,
+            vec![Word::from_static("version")],
+        ));
+        let size = buf.len();
+        buf.truncate(size - 1);
+
+        assert_eq!(Packet::read(&mut buf, Packet::MAX_SIZE).unwrap(), None);
+        assert_eq!(buf.len(), size - 1);
+    }
+
+    #[test]
+    fn size_exceeds_max() {
+        let mut buf = header(0, Packet::MAX_SIZE as u32 + 1, 0);
+        let err = Packet::read(&mut buf, Packet::MAX_SIZE).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn size_smaller_than_header() {
+        let mut buf = header(0, Packet::HEADER_SIZE as u32 - 1, 0);
+        let err = Packet::read(&mut buf, Packet::MAX_SIZE).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn word_count_exceeds_body() {
+        // Room for a single empty word, while two are declared.
+        let mut buf = header(0, Packet::HEADER_SIZE as u32 + 5, 2);
+        let err = Packet::read(&mut buf, Packet::MAX_SIZE).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn word_size_exceeds_body() {
+        let mut buf = header(0, Packet::HEADER_SIZE as u32 + 5, 1);
+        buf.put_u32_le(1);
+        buf.put_u8(0);
+        let err = Packet::read(&mut buf, Packet::MAX_SIZE).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
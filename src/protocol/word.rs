@@ -0,0 +1,88 @@
+use std::{fmt, str};
+
+use bytes::Bytes;
+
+/// A word of a [`Packet`](super::Packet), which isn't necessarily valid UTF-8.
+///
+/// Decoded words share the buffer their packet was read into, so reading a packet doesn't
+/// allocate per word.
+#[derive(Clone, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Word(Bytes);
+
+impl Word {
+    pub const fn from_static(word: &'static str) -> Word {
+        Word(Bytes::from_static(word.as_bytes()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the word as a string slice, if it is valid UTF-8.
+    pub fn to_str(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(&self.0)
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_str() {
+            Ok(word) => fmt::Debug::fmt(word, f),
+            Err(_) => fmt::Debug::fmt(&self.0, f),
+        }
+    }
+}
+
+impl AsRef<[u8]> for Word {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Bytes> for Word {
+    fn from(word: Bytes) -> Word {
+        Word(word)
+    }
+}
+
+impl From<Vec<u8>> for Word {
+    fn from(word: Vec<u8>) -> Word {
+        Word(Bytes::from(word))
+    }
+}
+
+impl From<String> for Word {
+    fn from(word: String) -> Word {
+        Word(Bytes::from(word))
+    }
+}
+
+impl From<&'static str> for Word {
+    fn from(word: &'static str) -> Word {
+        Word::from_static(word)
+    }
+}
+
+impl PartialEq<str> for Word {
+    fn eq(&self, other: &str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for Word {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
@@ -0,0 +1,216 @@
+use std::{cmp, time::Duration};
+
+use tokio::{
+    sync::Mutex,
+    time::{self, Instant},
+};
+
+/// Token bucket limiting how fast requests are sent to the remote host.
+///
+/// Up to `burst` requests may be sent at once, after which one more request becomes available
+/// every `interval`. Requests over the limit wait for their turn instead of failing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub interval: Duration,
+}
+
+impl RateLimit {
+    pub fn new(burst: u32, interval: Duration) -> RateLimit {
+        RateLimit { burst, interval }
+    }
+}
+
+/// Budgets shared by every clone of a [`Client`](crate::Client).
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    chat: Option<Mutex<Bucket>>,
+    other: Option<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(chat: Option<RateLimit>, other: Option<RateLimit>) -> RateLimiter {
+        RateLimiter {
+            chat: chat.map(|limit| Mutex::new(Bucket::new(limit))),
+            other: other.map(|limit| Mutex::new(Bucket::new(limit))),
+        }
+    }
+
+    /// Waits until the budget the command belongs to allows sending it.
+    pub(crate) async fn acquire(&self, words: &[String]) {
+        let is_chat = matches!(
+            words.first().map(String::as_str),
+            Some("admin.say") | Some("admin.yell")
+        );
+
+        let bucket = if is_chat { &self.chat } else { &self.other };
+        if let Some(bucket) = bucket {
+            // The lock is held while sleeping, so that waiting requests are sent in order.
+            bucket.lock().await.acquire().await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: u32,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(mut limit: RateLimit) -> Bucket {
+        limit.burst = cmp::max(limit.burst, 1);
+        Bucket {
+            limit,
+            tokens: limit.burst,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    async fn acquire(&mut self) {
+        if self.limit.interval == Duration::from_secs(0) {
+            return;
+        }
+
+        self.refill(Instant::now());
+        if self.tokens == 0 {
+            let next_token_at = self.refilled_at + self.limit.interval;
+            time::sleep_until(next_token_at).await;
+            self.refill(next_token_at);
+        }
+        self.tokens = self.tokens.saturating_sub(1);
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        let new_tokens = elapsed.as_nanos() / self.limit.interval.as_nanos();
+        if new_tokens == 0 {
+            return;
+        }
+
+        let tokens = cmp::min(
+            u128::from(self.tokens) + new_tokens,
+            u128::from(self.limit.burst),
+        );
+        self.tokens = tokens as u32;
+        if self.tokens == self.limit.burst {
+            self.refilled_at = now;
+        } else {
+            self.refilled_at += self.limit.interval * new_tokens as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(command: &str) -> Vec<String> {
+        vec![command.to_owned()]
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn burst_then_interval() {
+        let mut bucket = Bucket::new(RateLimit::new(3, Duration::from_secs(1)));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            bucket.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(0));
+
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refill_is_capped_at_burst() {
+        let mut bucket = Bucket::new(RateLimit::new(2, Duration::from_secs(1)));
+        bucket.acquire().await;
+        bucket.acquire().await;
+
+        time::advance(Duration::from_secs(10)).await;
+        let start = Instant::now();
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(0));
+
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn partial_refill() {
+        let mut bucket = Bucket::new(RateLimit::new(3, Duration::from_secs(1)));
+        let start = Instant::now();
+        for _ in 0..3 {
+            bucket.acquire().await;
+        }
+
+        // Two tokens have been refilled, and the third is half way there.
+        time::advance(Duration::from_millis(2500)).await;
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(2500));
+
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_burst_allows_one() {
+        let mut bucket = Bucket::new(RateLimit::new(0, Duration::from_secs(1)));
+        let start = Instant::now();
+
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(0));
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_interval_is_unlimited() {
+        let mut bucket = Bucket::new(RateLimit::new(1, Duration::from_secs(0)));
+        let start = Instant::now();
+
+        for _ in 0..10 {
+            bucket.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn chat_has_its_own_budget() {
+        let limiter = RateLimiter::new(
+            Some(RateLimit::new(1, Duration::from_secs(60))),
+            Some(RateLimit::new(1, Duration::from_secs(1))),
+        );
+        let start = Instant::now();
+
+        limiter.acquire(&words("admin.say")).await;
+        limiter.acquire(&words("version")).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(0));
+
+        limiter.acquire(&words("serverInfo")).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        limiter.acquire(&words("admin.yell")).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unlimited_budget() {
+        let limiter = RateLimiter::new(Some(RateLimit::new(1, Duration::from_secs(60))), None);
+        let start = Instant::now();
+
+        for _ in 0..10 {
+            limiter.acquire(&words("version")).await;
+        }
+        limiter.acquire(&words("admin.say")).await;
+        limiter.acquire(&[]).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(0));
+    }
+}
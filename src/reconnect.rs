@@ -0,0 +1,142 @@
+use std::{cmp, collections::VecDeque, net::SocketAddr, time::Duration};
+
+use tokio::{sync::broadcast, time};
+
+use crate::{
+    connection::{Config, RequestRx},
+    Connection, Error, EventSender, Message, Request,
+};
+
+/// Configures how a [`Client`](crate::Client) re-establishes a lost connection.
+///
+/// The login and events settings of the [`ClientBuilder`](crate::ClientBuilder) are restored on
+/// every reconnection.
+#[derive(Clone, Debug)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnection attempt, doubled after every failed attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between reconnection attempts.
+    pub max_backoff: Duration,
+    /// Number of attempts after which the client gives up, or `None` to retry forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_attempts: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConnectionState {
+    /// The connection has been re-established and the session restored.
+    Connected,
+    /// The connection to the remote host has been lost.
+    Disconnected,
+    /// About to make the specified reconnection attempt, counting from 1.
+    Reconnecting { attempt: u32 },
+}
+
+/// Drives connections to the remote host until every client has been dropped, or the policy
+/// gives up on reconnecting.
+pub(crate) async fn run(
+    addrs: Vec<SocketAddr>,
+    config: Config,
+    policy: ReconnectPolicy,
+    mut connection: Connection,
+    event_tx: EventSender,
+    state_tx: broadcast::Sender<ConnectionState>,
+    mut request_rx: RequestRx,
+) {
+    let mut stash = VecDeque::new();
+
+    loop {
+        if connection.run(&mut request_rx).await.is_ok() {
+            return;
+        }
+        let _ = state_tx.send(ConnectionState::Disconnected);
+
+        connection = match reconnect(
+            &addrs,
+            &config,
+            &policy,
+            &event_tx,
+            &state_tx,
+            &mut request_rx,
+            &mut stash,
+        )
+        .await
+        {
+            Some(connection) => connection,
+            None => return,
+        };
+        let _ = state_tx.send(ConnectionState::Connected);
+    }
+}
+
+async fn reconnect(
+    addrs: &[SocketAddr],
+    config: &Config,
+    policy: &ReconnectPolicy,
+    event_tx: &EventSender,
+    state_tx: &broadcast::Sender<ConnectionState>,
+    request_rx: &mut RequestRx,
+    stash: &mut VecDeque<Request>,
+) -> Option<Connection> {
+    let mut backoff = policy.initial_backoff;
+
+    for attempt in 1.. {
+        if policy.max_attempts.is_some_and(|max| attempt > max) {
+            return None;
+        }
+        let _ = state_tx.send(ConnectionState::Reconnecting { attempt });
+
+        let sleep = time::sleep(backoff);
+        tokio::pin!(sleep);
+        loop {
+            // Once the stash is as large as the queue, requests are left in the queue so that
+            // sending them waits for the connection to be restored.
+            stash.retain(|(_, response_tx)| !response_tx.is_closed());
+            let has_room = request_rx.bound().is_none_or(|bound| stash.len() < bound);
+
+            tokio::select! {
+                _ = &mut sleep => break,
+
+                message = request_rx.recv(), if has_room => {
+                    match message {
+                        Some(Message::Request(request)) => stash.push_back(request),
+                        // Without a connection there is nothing left to end gracefully.
+                        Some(Message::Shutdown(done_tx)) => {
+                            let _ = done_tx.send(());
+                            return None;
+                        }
+                        None => return None,
+                    }
+                }
+            }
+        }
+        backoff = cmp::min(backoff.saturating_mul(2), policy.max_backoff);
+
+        if let Ok(mut connection) = Connection::connect(addrs, config, event_tx.clone()).await {
+            // Requests made while reconnecting are sent as soon as the session is restored.
+            if flush(&mut connection, stash).await.is_ok() {
+                return Some(connection);
+            }
+        }
+    }
+
+    None
+}
+
+async fn flush(connection: &mut Connection, stash: &mut VecDeque<Request>) -> Result<(), Error> {
+    while let Some(request) = stash.pop_front() {
+        connection.wait_for_capacity().await?;
+        connection.handle_request(request).await?;
+    }
+
+    Ok(())
+}
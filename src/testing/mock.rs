@@ -0,0 +1,381 @@
+use std::{
+    collections::HashMap,
+    fmt, io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{broadcast, watch},
+    task::JoinHandle,
+    time,
+};
+use tokio_util::codec::Framed;
+
+use crate::{
+    client::hash_password,
+    protocol::{Codec, Packet, Word, WordDecoding},
+};
+
+type Responder = Arc<dyn Fn(&[String]) -> Vec<String> + Send + Sync>;
+
+/// Commands that may be sent before logging in.
+const PUBLIC_COMMANDS: [&str; 7] = [
+    "login.plainText",
+    "login.hashed",
+    "logout",
+    "quit",
+    "listPlayers",
+    "serverInfo",
+    "version",
+];
+
+/// Scripted RCON server listening on localhost.
+///
+/// Logging in, `logout`, `quit` and `admin.eventsEnabled` are handled like a real server would,
+/// while any other command is answered by the responses scripted for it. Commands without a
+/// scripted response are answered with `UnknownCommand`, except for `version`.
+///
+/// The server stops once dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    event_tx: broadcast::Sender<Vec<String>>,
+    clients_rx: watch::Receiver<usize>,
+    /// Closes every session once dropped, since responders may keep the state alive.
+    _shutdown_tx: watch::Sender<()>,
+    accept_handle: JoinHandle<()>,
+}
+
+struct State {
+    password: Option<String>,
+    responders: HashMap<String, Responder>,
+    requests: Vec<Vec<String>>,
+    responses: Vec<Packet>,
+    /// Number of sessions that enabled events.
+    event_clients: usize,
+}
+
+impl MockServer {
+    /// Starts listening on a free port of localhost.
+    pub async fn start() -> io::Result<MockServer> {
+        MockServer::bind(([127, 0, 0, 1], 0).into()).await
+    }
+
+    /// Starts listening on the address, such as that of a dropped server to simulate a restart.
+    pub async fn bind(addr: SocketAddr) -> io::Result<MockServer> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            password: None,
+            responders: HashMap::new(),
+            requests: Vec::new(),
+            responses: Vec::new(),
+            event_clients: 0,
+        }));
+        let (event_tx, _) = broadcast::channel(1000);
+        let (clients_tx, clients_rx) = watch::channel(0);
+        let (shutdown_tx, shutdown_rx) = watch::channel(());
+
+        let accept_handle = tokio::spawn(accept(
+            listener,
+            state.clone(),
+            event_tx.clone(),
+            clients_tx,
+            shutdown_rx,
+        ));
+
+        Ok(MockServer {
+            addr,
+            state,
+            event_tx,
+            clients_rx,
+            _shutdown_tx: shutdown_tx,
+            accept_handle,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Requires clients to log in with the password, using either login method.
+    pub fn set_password(&self, password: Option<String>) {
+        self.state.lock().unwrap().password = password;
+    }
+
+    /// Answers every request for the command with the words, starting with the status.
+    pub fn respond(&self, command: &str, response: Vec<String>) {
+        self.respond_with(command, move |_| response.clone());
+    }
+
+    /// Answers every request for the command with the words returned by the function, which
+    /// receives the words of the request.
+    pub fn respond_with<F>(&self, command: &str, responder: F)
+    where
+        F: Fn(&[String]) -> Vec<String> + Send + Sync + 'static,
+    {
+        self.state
+            .lock()
+            .unwrap()
+            .responders
+            .insert(command.to_owned(), Arc::new(responder));
+    }
+
+    /// Returns every request received so far, in order.
+    pub fn requests(&self) -> Vec<Vec<String>> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns every response received so far, such as acknowledgements of events, in order.
+    pub fn responses(&self) -> Vec<Packet> {
+        self.state.lock().unwrap().responses.clone()
+    }
+
+    /// Sends the event to every connected client that enabled events, returning the number of
+    /// those clients.
+    pub fn push_event(&self, words: Vec<String>) -> usize {
+        let _ = self.event_tx.send(words);
+        self.state.lock().unwrap().event_clients
+    }
+
+    pub(crate) fn event_tx(&self) -> broadcast::Sender<Vec<String>> {
+        self.event_tx.clone()
+    }
+
+    /// Completes once at least the given number of clients are connected.
+    pub async fn wait_for_clients(&self, count: usize) {
+        let mut clients_rx = self.clients_rx.clone();
+        let _ = clients_rx.wait_for(|&clients| clients >= count).await;
+    }
+}
+
+impl fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockServer")
+            .field("addr", &self.addr)
+            .finish()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.accept_handle.abort();
+    }
+}
+
+async fn accept(
+    listener: TcpListener,
+    state: Arc<Mutex<State>>,
+    event_tx: broadcast::Sender<Vec<String>>,
+    clients_tx: watch::Sender<usize>,
+    shutdown_rx: watch::Receiver<()>,
+) {
+    let clients_tx = Arc::new(clients_tx);
+
+    for session_id in 0_u64.. {
+        let tcp_stream = match listener.accept().await {
+            Ok((tcp_stream, _)) => tcp_stream,
+            // Errors such as running out of file descriptors persist for a while, so accepting
+            // is paused instead of retried right away.
+            Err(_) => {
+                time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        // Subscribing before the client is counted, so that no event pushed afterwards is lost.
+        let event_rx = event_tx.subscribe();
+        clients_tx.send_modify(|clients| *clients += 1);
+
+        let state = state.clone();
+        let clients_tx = clients_tx.clone();
+        let shutdown_rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            serve(tcp_stream, state, event_rx, shutdown_rx, session_id).await;
+            clients_tx.send_modify(|clients| *clients -= 1);
+        });
+    }
+}
+
+struct Session {
+    state: Arc<Mutex<State>>,
+    logged_in: bool,
+    events_enabled: bool,
+    salt: Option<String>,
+    session_id: u64,
+}
+
+impl Session {
+    fn set_events_enabled(&mut self, state: &mut State, enabled: bool) {
+        if enabled != self.events_enabled {
+            self.events_enabled = enabled;
+            if enabled {
+                state.event_clients += 1;
+            } else {
+                state.event_clients -= 1;
+            }
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if self.events_enabled {
+            self.state.lock().unwrap().event_clients -= 1;
+        }
+    }
+}
+
+async fn serve(
+    tcp_stream: TcpStream,
+    state: Arc<Mutex<State>>,
+    mut event_rx: broadcast::Receiver<Vec<String>>,
+    mut shutdown_rx: watch::Receiver<()>,
+    session_id: u64,
+) {
+    let mut stream = Framed::new(tcp_stream, Codec::new());
+    let mut session = Session {
+        state: state.clone(),
+        logged_in: false,
+        events_enabled: false,
+        salt: None,
+        session_id,
+    };
+    let mut next_event_id = 0_u32;
+
+    loop {
+        tokio::select! {
+            packet = stream.next() => {
+                let packet = match packet {
+                    Some(Ok(packet)) => packet,
+                    _ => return,
+                };
+                // Acknowledgements of events need no answer.
+                if packet.is_response {
+                    state.lock().unwrap().responses.push(packet);
+                    continue;
+                }
+
+                let words: Vec<String> = packet
+                    .words
+                    .iter()
+                    .filter_map(|word| WordDecoding::Lossy.decode(word).ok())
+                    .collect();
+                let is_quit = words.first().map(String::as_str) == Some("quit");
+                let response = respond(&state, &mut session, words);
+
+                let response = Packet::new(
+                    packet.id,
+                    true,
+                    packet.is_from_server,
+                    response.into_iter().map(Word::from).collect(),
+                );
+                if stream.send(response).await.is_err() || is_quit {
+                    return;
+                }
+            }
+
+            event = event_rx.recv() => {
+                let words = match event {
+                    Ok(words) if session.events_enabled => words,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+
+                let event = Packet::new(
+                    next_event_id,
+                    false,
+                    true,
+                    words.into_iter().map(Word::from).collect(),
+                );
+                next_event_id = next_event_id.wrapping_add(1);
+                if stream.send(event).await.is_err() {
+                    return;
+                }
+            }
+
+            // Nothing is ever sent, so this only completes once the server is dropped.
+            _ = shutdown_rx.changed() => return,
+        }
+    }
+}
+
+fn respond(state: &Mutex<State>, session: &mut Session, words: Vec<String>) -> Vec<String> {
+    let mut state = state.lock().unwrap();
+    state.requests.push(words.clone());
+
+    let command = match words.first() {
+        Some(command) => command.as_str(),
+        None => return status("UnknownCommand"),
+    };
+    let logged_in = session.logged_in || state.password.is_none();
+
+    match (command, &words[1..]) {
+        ("login.plainText", [_]) | ("login.hashed", [_]) if state.password.is_none() => {
+            status("PasswordNotSet")
+        }
+        ("login.plainText", [password]) => match state.password {
+            Some(ref expected) if expected != password => status("InvalidPassword"),
+            _ => {
+                session.logged_in = true;
+                status("OK")
+            }
+        },
+        ("login.hashed", []) => {
+            let seed = format!("{}{:?}", session.session_id, SystemTime::now());
+            let salt = format!("{:X}", md5::compute(seed));
+            session.salt = Some(salt.clone());
+            vec!["OK".to_owned(), salt]
+        }
+        ("login.hashed", [hash]) => {
+            let expected = match (&session.salt, &state.password) {
+                (Some(salt), Some(password)) => hash_password(salt, password).ok(),
+                _ => None,
+            };
+
+            if expected.as_ref() == Some(hash) {
+                session.logged_in = true;
+                status("OK")
+            } else {
+                status("InvalidPasswordHash")
+            }
+        }
+        ("login.plainText", _) | ("login.hashed", _) => status("InvalidArguments"),
+        ("logout", []) => {
+            session.logged_in = false;
+            status("OK")
+        }
+        ("quit", []) => status("OK"),
+        (command, _) if !logged_in && !PUBLIC_COMMANDS.contains(&command) => {
+            status("LogInRequired")
+        }
+        ("admin.eventsEnabled", []) => vec!["OK".to_owned(), session.events_enabled.to_string()],
+        ("admin.eventsEnabled", [enabled]) => match enabled.parse() {
+            Ok(enabled) => {
+                session.set_events_enabled(&mut state, enabled);
+                status("OK")
+            }
+            Err(_) => status("InvalidArguments"),
+        },
+        ("admin.eventsEnabled", _) => status("InvalidArguments"),
+        (command, _) => match state.responders.get(command) {
+            Some(responder) => {
+                let responder = responder.clone();
+                drop(state);
+                responder(&words)
+            }
+            None => match command {
+                "version" => vec!["OK".to_owned(), "BF4".to_owned(), "0".to_owned()],
+                _ => status("UnknownCommand"),
+            },
+        },
+    }
+}
+
+fn status(status: &str) -> Vec<String> {
+    vec![status.to_owned()]
+}
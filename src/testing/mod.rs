@@ -0,0 +1,8 @@
+//! Servers speaking the RCON protocol, to test code built on [`Client`](crate::Client) without
+//! a game server.
+
+mod mock;
+pub use self::mock::MockServer;
+
+mod simulator;
+pub use self::simulator::Simulator;
@@ -0,0 +1,1164 @@
+use std::{
+    cmp,
+    collections::HashMap,
+    fmt, io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::broadcast, task::JoinHandle, time};
+
+use crate::models::{
+    BanEntry, BanTimeout, Faction, GameAdmin, IdType, PlayerInfo, PlayerKind, Preset, PresetKind,
+    ServerType, UnlockMode, Var, VarName,
+};
+
+use super::MockServer;
+
+const COMMANDS: [&str; 44] = [
+    "serverInfo",
+    "listPlayers",
+    "admin.listPlayers",
+    "admin.say",
+    "admin.yell",
+    "admin.movePlayer",
+    "admin.killPlayer",
+    "admin.kickPlayer",
+    "banList.add",
+    "banList.remove",
+    "banList.clear",
+    "banList.list",
+    "banList.load",
+    "banList.save",
+    "reservedSlotsList.add",
+    "reservedSlotsList.remove",
+    "reservedSlotsList.clear",
+    "reservedSlotsList.list",
+    "reservedSlotsList.load",
+    "reservedSlotsList.save",
+    "reservedSlotsList.aggressiveJoin",
+    "spectatorList.add",
+    "spectatorList.remove",
+    "spectatorList.clear",
+    "spectatorList.list",
+    "spectatorList.load",
+    "spectatorList.save",
+    "gameAdmin.add",
+    "gameAdmin.remove",
+    "gameAdmin.clear",
+    "gameAdmin.list",
+    "gameAdmin.load",
+    "gameAdmin.save",
+    "mapList.add",
+    "mapList.remove",
+    "mapList.clear",
+    "mapList.list",
+    "mapList.getMapIndices",
+    "mapList.setNextMapIndex",
+    "mapList.load",
+    "mapList.save",
+    "mapList.endRound",
+    "mapList.restartRound",
+    "mapList.runNextRound",
+];
+
+const PLAYER_FIELDS: [&str; 10] = [
+    "name", "guid", "teamId", "squadId", "kills", "deaths", "score", "rank", "ping", "type",
+];
+
+const WEAPONS: [&str; 8] = [
+    "U_AEK971", "U_M416", "U_SCAR-H", "U_ACR", "U_M98B", "U_870", "U_M67", "Melee",
+];
+
+const CHAT_MESSAGES: [&str; 4] = ["gg", "nice shot", "push B", "lol"];
+
+const TICKETS: f32 = 800.0;
+
+/// Stateful BF4 server listening on localhost, for exercising code built on
+/// [`Client`](crate::Client) without a game server.
+///
+/// Every command [`Client`](crate::Client) supports is answered from the simulated state of
+/// the server, while players, kills and rounds are driven either by hand or randomly using
+/// [`Simulator::start_activity`]. Changes are announced with the events a BF4 server sends.
+///
+/// The underlying [`MockServer`] can be used to script further commands or to inspect the
+/// requests received. The simulator stops once dropped.
+pub struct Simulator {
+    server: MockServer,
+    world: Arc<Mutex<World>>,
+    event_tx: broadcast::Sender<Vec<String>>,
+    activity_handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Simulator {
+    /// Starts listening on a free port of localhost, with no players and a default map list.
+    pub async fn start() -> io::Result<Simulator> {
+        let server = MockServer::start().await?;
+        let world = Arc::new(Mutex::new(World::new()));
+        let event_tx = server.event_tx();
+
+        let commands = COMMANDS
+            .iter()
+            .copied()
+            .chain(VarName::ALL.iter().map(|name| name.as_str()));
+        for command in commands {
+            let world = world.clone();
+            let event_tx = event_tx.clone();
+            server.respond_with(command, move |words| {
+                let mut events = Vec::new();
+                let response = world.lock().unwrap().handle(words, &mut events);
+                for event in events {
+                    let _ = event_tx.send(event);
+                }
+
+                response
+            });
+        }
+
+        Ok(Simulator {
+            server,
+            world,
+            event_tx,
+            activity_handle: Mutex::new(None),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.server.addr()
+    }
+
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// Sets a variable as if it had been configured before the server started, which also
+    /// works for startup-only variables.
+    pub fn set_var(&self, var: Var) {
+        self.update(|world, _| {
+            world.vars.insert(var.name(), var);
+        });
+    }
+
+    /// Returns the players currently on the server.
+    pub fn players(&self) -> Vec<PlayerInfo> {
+        self.world.lock().unwrap().players.clone()
+    }
+
+    /// Lets a player join the team with fewer players, unless the server is full.
+    pub fn player_join(&self, name: &str) {
+        self.update(|world, events| world.join(name.to_owned(), events));
+    }
+
+    pub fn player_leave(&self, name: &str) {
+        self.update(|world, events| world.leave(name, events));
+    }
+
+    pub fn player_spawn(&self, name: &str) {
+        self.update(|world, events| {
+            if let Some(player) = world.player(name) {
+                events.push(words(&[
+                    "player.onSpawn",
+                    name,
+                    &player.team_id.to_string(),
+                ]));
+            }
+        });
+    }
+
+    /// Lets one player kill another, ending the round once a team runs out of tickets.
+    pub fn player_kill(&self, killer: &str, victim: &str, weapon: &str, headshot: bool) {
+        self.update(|world, events| world.kill(killer, victim, weapon, headshot, events));
+    }
+
+    /// Sends a chat message from a player to everyone.
+    pub fn player_chat(&self, name: &str, message: &str) {
+        self.update(|world, events| {
+            if world.player(name).is_some() {
+                events.push(words(&["player.onChat", name, message, "all"]));
+            }
+        });
+    }
+
+    /// Ends the round, moving on to the next map once every round of the current one has been
+    /// played.
+    pub fn end_round(&self, winner: usize) {
+        self.update(|world, events| world.end_round(winner, events));
+    }
+
+    /// Makes a random player join, leave, chat or kill another player.
+    pub fn random_event(&self) {
+        self.update(|world, events| world.random_event(events));
+    }
+
+    /// Makes a random event happen every interval, until stopped or the simulator is dropped.
+    pub fn start_activity(&self, interval: Duration) {
+        let world = self.world.clone();
+        let event_tx = self.event_tx.clone();
+        let handle = tokio::spawn(async move {
+            let mut interval = time::interval(interval);
+            loop {
+                interval.tick().await;
+
+                let mut events = Vec::new();
+                world.lock().unwrap().random_event(&mut events);
+                for event in events {
+                    let _ = event_tx.send(event);
+                }
+            }
+        });
+
+        if let Some(handle) = self.activity_handle.lock().unwrap().replace(handle) {
+            handle.abort();
+        }
+    }
+
+    pub fn stop_activity(&self) {
+        if let Some(handle) = self.activity_handle.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut World, &mut Vec<Vec<String>>),
+    {
+        let mut events = Vec::new();
+        f(&mut self.world.lock().unwrap(), &mut events);
+        for event in events {
+            let _ = self.event_tx.send(event);
+        }
+    }
+}
+
+impl fmt::Debug for Simulator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Simulator")
+            .field("addr", &self.addr())
+            .finish()
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.stop_activity();
+    }
+}
+
+/// Lists kept by the server, which `load` and `save` copy from and to disk.
+#[derive(Clone, Default)]
+struct Lists {
+    maps: Vec<(String, String, usize)>,
+    bans: Vec<BanEntry>,
+    reserved_slots: Vec<String>,
+    spectators: Vec<String>,
+    game_admins: Vec<GameAdmin>,
+}
+
+struct World {
+    rng: u64,
+    next_player_id: u64,
+    players: Vec<PlayerInfo>,
+    lists: Lists,
+    saved_lists: Lists,
+    aggressive_join: bool,
+    vars: HashMap<VarName, Var>,
+    map_index: usize,
+    next_map_index: usize,
+    rounds_played: usize,
+    team_scores: [f32; 2],
+    started_at: Instant,
+    round_started_at: Instant,
+}
+
+impl World {
+    fn new() -> World {
+        let maps = [
+            "MP_Prison",
+            "MP_Siege",
+            "MP_Resort",
+            "MP_Damage",
+            "MP_Flooded",
+        ]
+        .iter()
+        .map(|map| (map.to_string(), "ConquestLarge0".to_owned(), 2))
+        .collect();
+        let lists = Lists {
+            maps,
+            ..Lists::default()
+        };
+
+        World {
+            rng: 0x2545_F491_4F6C_DD1D,
+            next_player_id: 1,
+            players: Vec::new(),
+            saved_lists: lists.clone(),
+            lists,
+            aggressive_join: false,
+            vars: VarName::ALL
+                .iter()
+                .map(|&name| (name, default_var(name)))
+                .collect(),
+            map_index: 0,
+            next_map_index: 1,
+            rounds_played: 0,
+            team_scores: [TICKETS; 2],
+            started_at: Instant::now(),
+            round_started_at: Instant::now(),
+        }
+    }
+
+    fn random(&mut self, bound: usize) -> usize {
+        // xorshift64*, which is plenty for picking players.
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let value = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D);
+
+        (value % cmp::max(bound, 1) as u64) as usize
+    }
+
+    fn player(&self, name: &str) -> Option<&PlayerInfo> {
+        self.players.iter().find(|player| player.name == name)
+    }
+
+    fn player_mut(&mut self, name: &str) -> Option<&mut PlayerInfo> {
+        self.players.iter_mut().find(|player| player.name == name)
+    }
+
+    fn max_players(&self) -> usize {
+        match self.vars.get(&VarName::MaxPlayers) {
+            Some(Var::MaxPlayers(count)) => *count as usize,
+            _ => 64,
+        }
+    }
+
+    fn join(&mut self, name: String, events: &mut Vec<Vec<String>>) {
+        if self.player(&name).is_some() || self.players.len() >= self.max_players() {
+            return;
+        }
+
+        let id = self.next_player_id;
+        self.next_player_id += 1;
+        let guid = format!("EA_{:032X}", id);
+
+        let team_size = |team_id| {
+            self.players
+                .iter()
+                .filter(|player| player.team_id == team_id)
+                .count()
+        };
+        let team_id = if team_size(1) <= team_size(2) { 1 } else { 2 };
+        let squad_id = cmp::min(team_size(team_id) / 5 + 1, 32);
+
+        let mut player = PlayerInfo::new(name.clone(), guid.clone());
+        player.team_id = team_id;
+        player.squad_id = squad_id;
+        player.rank = self.random(140) as i16 + 1;
+        player.ping = self.random(100) as u16 + 20;
+        self.players.push(player);
+
+        let (team_id, squad_id) = (team_id.to_string(), squad_id.to_string());
+        events.push(words(&["player.onJoin", &name, &guid]));
+        events.push(words(&["player.onAuthenticated", &name]));
+        events.push(words(&["player.onTeamChange", &name, &team_id, &squad_id]));
+        events.push(words(&["player.onSquadChange", &name, &team_id, &squad_id]));
+    }
+
+    fn leave(&mut self, name: &str, events: &mut Vec<Vec<String>>) {
+        if let Some(index) = self.players.iter().position(|player| player.name == name) {
+            let player = self.players.remove(index);
+
+            let mut event = words(&["player.onLeave", name]);
+            event.extend(player_block(&[player]));
+            events.push(event);
+        }
+    }
+
+    fn kill(
+        &mut self,
+        killer: &str,
+        victim: &str,
+        weapon: &str,
+        headshot: bool,
+        events: &mut Vec<Vec<String>>,
+    ) {
+        let victim_team = match self.player(victim) {
+            Some(player) => player.team_id,
+            None => return,
+        };
+        let killer_team = match self.player(killer) {
+            Some(player) => player.team_id,
+            None => return,
+        };
+
+        if let Some(player) = self.player_mut(killer) {
+            if killer_team == victim_team {
+                player.score = player.score.saturating_sub(100);
+            } else {
+                player.kills += 1;
+                player.score += if headshot { 110 } else { 100 };
+            }
+        }
+        if let Some(player) = self.player_mut(victim) {
+            player.deaths += 1;
+        }
+
+        events.push(words(&[
+            "player.onKill",
+            killer,
+            victim,
+            weapon,
+            &headshot.to_string(),
+        ]));
+
+        if let Some(tickets) = self.team_scores.get_mut(victim_team.wrapping_sub(1)) {
+            *tickets -= 1.0;
+            if *tickets <= 0.0 {
+                let winner = if victim_team == 1 { 2 } else { 1 };
+                self.end_round(winner, events);
+            }
+        }
+    }
+
+    fn end_round(&mut self, winner: usize, events: &mut Vec<Vec<String>>) {
+        events.push(words(&["server.onRoundOver", &winner.to_string()]));
+
+        let mut event = words(&["server.onRoundOverPlayers"]);
+        event.extend(player_block(&self.players));
+        events.push(event);
+
+        let mut event = words(&["server.onRoundOverTeamScores", "2"]);
+        event.extend(self.team_scores.iter().map(|score| score.to_string()));
+        event.push("0".to_owned());
+        events.push(event);
+
+        self.rounds_played += 1;
+        let rounds_total = self.lists.maps.get(self.map_index).map_or(0, |map| map.2);
+        if self.rounds_played >= rounds_total {
+            self.advance_map();
+        }
+        self.load_level(events);
+    }
+
+    fn advance_map(&mut self) {
+        self.map_index = self.next_map_index;
+        self.next_map_index = match self.lists.maps.len() {
+            0 => 0,
+            len => (self.map_index + 1) % len,
+        };
+        self.rounds_played = 0;
+    }
+
+    fn load_level(&mut self, events: &mut Vec<Vec<String>>) {
+        for player in &mut self.players {
+            player.kills = 0;
+            player.deaths = 0;
+            player.score = 0;
+        }
+        self.team_scores = [TICKETS; 2];
+        self.round_started_at = Instant::now();
+
+        let (map, gamemode, rounds_total) = self.current_map();
+        events.push(words(&[
+            "server.onLevelLoaded",
+            &map,
+            &gamemode,
+            &self.rounds_played.to_string(),
+            &rounds_total.to_string(),
+        ]));
+    }
+
+    fn current_map(&self) -> (String, String, usize) {
+        self.lists
+            .maps
+            .get(self.map_index)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn random_event(&mut self, events: &mut Vec<Vec<String>>) {
+        let count = self.players.len();
+        let roll = self.random(100);
+
+        if count < 4 || (roll < 10 && count < self.max_players()) {
+            let name = format!("Player{}", self.next_player_id);
+            self.join(name, events);
+        } else if roll < 13 {
+            let index = self.random(count);
+            let name = self.players[index].name.clone();
+            self.leave(&name, events);
+        } else if roll < 20 {
+            let index = self.random(count);
+            let name = self.players[index].name.clone();
+            let message = CHAT_MESSAGES[self.random(CHAT_MESSAGES.len())];
+            events.push(words(&["player.onChat", &name, message, "all"]));
+        } else if roll < 35 {
+            let index = self.random(count);
+            let player = &self.players[index];
+            let (name, team_id) = (player.name.clone(), player.team_id.to_string());
+            events.push(words(&["player.onSpawn", &name, &team_id]));
+        } else {
+            let index = self.random(count);
+            let killer = self.players[index].clone();
+            let enemies: Vec<_> = self
+                .players
+                .iter()
+                .filter(|player| player.team_id != killer.team_id)
+                .map(|player| player.name.clone())
+                .collect();
+            if enemies.is_empty() {
+                return;
+            }
+
+            let victim = enemies[self.random(enemies.len())].clone();
+            let weapon = WEAPONS[self.random(WEAPONS.len())];
+            let headshot = self.random(5) == 0;
+            self.kill(&killer.name, &victim, weapon, headshot, events);
+        }
+    }
+
+    fn handle(&mut self, request: &[String], events: &mut Vec<Vec<String>>) -> Vec<String> {
+        let command = request[0].as_str();
+        let args: Vec<&str> = request[1..].iter().map(String::as_str).collect();
+
+        if let Ok(name) = command.parse::<VarName>() {
+            return self.handle_var(name, &request[1..]);
+        }
+
+        match (command, args.as_slice()) {
+            ("serverInfo", []) => self.server_info(),
+            ("listPlayers", subset) => match self.filter_players(subset) {
+                Some(mut players) => {
+                    for player in &mut players {
+                        player.guid.clear();
+                    }
+
+                    let mut response = ok();
+                    response.extend(player_block(&players));
+                    response
+                }
+                None => status("InvalidArguments"),
+            },
+            ("admin.listPlayers", subset) => match self.filter_players(subset) {
+                Some(players) => {
+                    let mut response = ok();
+                    response.extend(player_block(&players));
+                    response
+                }
+                None => status("InvalidArguments"),
+            },
+            ("admin.say", [message, subset @ ..]) => self.message(message, subset),
+            ("admin.yell", [message, duration, subset @ ..]) if duration.parse::<u64>().is_ok() => {
+                self.message(message, subset)
+            }
+            ("admin.movePlayer", [name, team_id, squad_id, force_kill]) => {
+                match (
+                    team_id.parse::<usize>(),
+                    squad_id.parse::<usize>(),
+                    force_kill.parse::<bool>(),
+                ) {
+                    (Ok(team_id), Ok(squad_id), Ok(_)) if (1..=2).contains(&team_id) => {
+                        self.move_player(name, team_id, squad_id, events)
+                    }
+                    (Ok(_), _, _) => status("InvalidTeamId"),
+                    _ => status("InvalidArguments"),
+                }
+            }
+            ("admin.killPlayer", [name]) => match self.player_mut(name) {
+                Some(player) => {
+                    player.deaths += 1;
+                    ok()
+                }
+                None => status("InvalidPlayerName"),
+            },
+            ("admin.kickPlayer", [name, ..]) => {
+                if self.player(name).is_none() {
+                    return status("InvalidPlayerName");
+                }
+
+                self.leave(name, events);
+                ok()
+            }
+
+            ("banList.add", [id_type, id, timeout @ ..]) => {
+                self.add_ban(id_type, id, timeout, events)
+            }
+            ("banList.remove", [id_type, id]) => {
+                let id_type = match id_type.parse::<IdType>() {
+                    Ok(id_type) => id_type,
+                    Err(_) => return status("InvalidIdType"),
+                };
+
+                let len = self.lists.bans.len();
+                self.lists
+                    .bans
+                    .retain(|ban| ban.id_type != id_type || ban.id != *id);
+                if self.lists.bans.len() == len {
+                    status("NotFound")
+                } else {
+                    ok()
+                }
+            }
+            ("banList.clear", []) => {
+                self.lists.bans.clear();
+                ok()
+            }
+            ("banList.list", offset) => match parse_offset(offset) {
+                Some(offset) => {
+                    let mut response = ok();
+                    for ban in self.lists.bans.iter().skip(offset).take(100) {
+                        response.extend(ban_words(ban));
+                    }
+                    response
+                }
+                None => status("InvalidArguments"),
+            },
+
+            ("reservedSlotsList.aggressiveJoin", []) => {
+                vec!["OK".to_owned(), self.aggressive_join.to_string()]
+            }
+            ("reservedSlotsList.aggressiveJoin", [enabled]) => match enabled.parse() {
+                Ok(enabled) => {
+                    self.aggressive_join = enabled;
+                    ok()
+                }
+                Err(_) => status("InvalidArguments"),
+            },
+            ("reservedSlotsList.add", [name]) => add_name(&mut self.lists.reserved_slots, name),
+            ("reservedSlotsList.remove", [name]) => {
+                remove_name(&mut self.lists.reserved_slots, name)
+            }
+            ("reservedSlotsList.clear", []) => {
+                self.lists.reserved_slots.clear();
+                ok()
+            }
+            ("reservedSlotsList.list", offset) => list_names(&self.lists.reserved_slots, offset),
+
+            ("spectatorList.add", [name]) => add_name(&mut self.lists.spectators, name),
+            ("spectatorList.remove", [name]) => remove_name(&mut self.lists.spectators, name),
+            ("spectatorList.clear", []) => {
+                self.lists.spectators.clear();
+                ok()
+            }
+            ("spectatorList.list", offset) => list_names(&self.lists.spectators, offset),
+
+            ("gameAdmin.add", [name, restriction_level]) => match restriction_level.parse() {
+                Ok(restriction_level) if restriction_level <= 3 => {
+                    self.lists.game_admins.retain(|admin| admin.name != *name);
+                    self.lists.game_admins.push(GameAdmin {
+                        name: name.to_string(),
+                        restriction_level,
+                    });
+                    ok()
+                }
+                _ => status("InvalidArguments"),
+            },
+            ("gameAdmin.remove", [name]) => {
+                let len = self.lists.game_admins.len();
+                self.lists.game_admins.retain(|admin| admin.name != *name);
+                if self.lists.game_admins.len() == len {
+                    status("PlayerNotInList")
+                } else {
+                    ok()
+                }
+            }
+            ("gameAdmin.clear", []) => {
+                self.lists.game_admins.clear();
+                ok()
+            }
+            ("gameAdmin.list", []) => {
+                let mut response = ok();
+                for admin in &self.lists.game_admins {
+                    response.push(admin.name.clone());
+                    response.push(admin.restriction_level.to_string());
+                }
+                response
+            }
+
+            ("mapList.add", [map, gamemode, rounds, index @ ..]) => {
+                let rounds = match rounds.parse() {
+                    Ok(rounds) => rounds,
+                    Err(_) => return status("InvalidRoundsPerMap"),
+                };
+                let index = match index {
+                    [] => self.lists.maps.len(),
+                    [index] => match index.parse() {
+                        Ok(index) if index <= self.lists.maps.len() => index,
+                        _ => return status("InvalidMapIndex"),
+                    },
+                    _ => return status("InvalidArguments"),
+                };
+
+                let map = (map.to_string(), gamemode.to_string(), rounds);
+                self.lists.maps.insert(index, map);
+                ok()
+            }
+            ("mapList.remove", [index]) => match index.parse() {
+                Ok(index) if index < self.lists.maps.len() => {
+                    self.lists.maps.remove(index);
+                    ok()
+                }
+                _ => status("InvalidMapIndex"),
+            },
+            ("mapList.clear", []) => {
+                self.lists.maps.clear();
+                ok()
+            }
+            ("mapList.list", offset) => match parse_offset(offset) {
+                Some(offset) => {
+                    let maps: Vec<_> = self.lists.maps.iter().skip(offset).take(100).collect();
+                    let mut response =
+                        vec!["OK".to_owned(), maps.len().to_string(), "3".to_owned()];
+                    for (map, gamemode, rounds) in maps {
+                        response.extend(vec![map.clone(), gamemode.clone(), rounds.to_string()]);
+                    }
+                    response
+                }
+                None => status("InvalidArguments"),
+            },
+            ("mapList.getMapIndices", []) => vec![
+                "OK".to_owned(),
+                self.map_index.to_string(),
+                self.next_map_index.to_string(),
+            ],
+            ("mapList.setNextMapIndex", [index]) => match index.parse() {
+                Ok(index) if index < self.lists.maps.len() => {
+                    self.next_map_index = index;
+                    ok()
+                }
+                _ => status("InvalidMapIndex"),
+            },
+            ("mapList.endRound", [winner]) => match winner.parse() {
+                Ok(winner) if winner <= 2 => {
+                    self.end_round(winner, events);
+                    ok()
+                }
+                _ => status("InvalidArguments"),
+            },
+            ("mapList.restartRound", []) => {
+                self.load_level(events);
+                ok()
+            }
+            ("mapList.runNextRound", []) => {
+                self.advance_map();
+                self.load_level(events);
+                ok()
+            }
+
+            (command, []) if command.ends_with(".load") => {
+                self.copy_list(command, false);
+                ok()
+            }
+            (command, []) if command.ends_with(".save") => {
+                self.copy_list(command, true);
+                ok()
+            }
+            _ => status("InvalidArguments"),
+        }
+    }
+
+    fn server_info(&self) -> Vec<String> {
+        let (map, gamemode, rounds_total) = self.current_map();
+        let has_password = match self.vars.get(&VarName::GamePassword) {
+            Some(Var::GamePassword(password)) => !password.is_empty(),
+            _ => false,
+        };
+        let name = match self.vars.get(&VarName::ServerName) {
+            Some(Var::ServerName(name)) => name.clone(),
+            _ => String::new(),
+        };
+
+        let mut response = vec![
+            "OK".to_owned(),
+            name,
+            self.players.len().to_string(),
+            self.max_players().to_string(),
+            gamemode,
+            map,
+            self.rounds_played.to_string(),
+            rounds_total.to_string(),
+            "2".to_owned(),
+        ];
+        response.extend(self.team_scores.iter().map(|score| score.to_string()));
+        response.extend(vec![
+            "0".to_owned(),
+            String::new(),
+            true.to_string(),
+            true.to_string(),
+            has_password.to_string(),
+            self.started_at.elapsed().as_secs().to_string(),
+            self.round_started_at.elapsed().as_secs().to_string(),
+            "127.0.0.1:25200".to_owned(),
+            "v1.905 | A1390 C2.351".to_owned(),
+            false.to_string(),
+            "EU".to_owned(),
+            "ams".to_owned(),
+            "NL".to_owned(),
+            self.players.len().to_string(),
+            "IN_GAME".to_owned(),
+        ]);
+
+        response
+    }
+
+    fn filter_players(&self, subset: &[&str]) -> Option<Vec<PlayerInfo>> {
+        let players = self.players.iter().filter(|player| match subset {
+            ["all"] => true,
+            ["team", team_id] => team_id.parse() == Ok(player.team_id),
+            ["squad", team_id, squad_id] => {
+                team_id.parse() == Ok(player.team_id) && squad_id.parse() == Ok(player.squad_id)
+            }
+            ["player", name] => player.name == *name,
+            _ => false,
+        });
+
+        match subset {
+            ["all"] | ["team", _] | ["squad", _, _] | ["player", _] => {
+                Some(players.cloned().collect())
+            }
+            _ => None,
+        }
+    }
+
+    fn message(&self, message: &str, subset: &[&str]) -> Vec<String> {
+        if message.chars().count() >= 256 {
+            return status("TooLongMessage");
+        }
+
+        match subset {
+            ["player", name] if self.player(name).is_none() => status("PlayerNotFound"),
+            _ => match self.filter_players(subset) {
+                Some(_) => ok(),
+                None => status("InvalidArguments"),
+            },
+        }
+    }
+
+    fn move_player(
+        &mut self,
+        name: &str,
+        team_id: usize,
+        squad_id: usize,
+        events: &mut Vec<Vec<String>>,
+    ) -> Vec<String> {
+        let player = match self.player_mut(name) {
+            Some(player) => player,
+            None => return status("InvalidPlayerName"),
+        };
+        if player.team_id == team_id && player.squad_id == squad_id {
+            return status("SetSquadFailed");
+        }
+
+        let changed_team = player.team_id != team_id;
+        player.team_id = team_id;
+        player.squad_id = squad_id;
+
+        let (team_id, squad_id) = (team_id.to_string(), squad_id.to_string());
+        if changed_team {
+            events.push(words(&["player.onTeamChange", name, &team_id, &squad_id]));
+        }
+        events.push(words(&["player.onSquadChange", name, &team_id, &squad_id]));
+
+        ok()
+    }
+
+    fn add_ban(
+        &mut self,
+        id_type: &str,
+        id: &str,
+        timeout: &[&str],
+        events: &mut Vec<Vec<String>>,
+    ) -> Vec<String> {
+        let id_type = match id_type.parse::<IdType>() {
+            Ok(id_type) => id_type,
+            Err(_) => return status("InvalidIdType"),
+        };
+        let (timeout, reason) = match timeout {
+            ["perm", reason @ ..] => (Some(BanTimeout::Permanent), reason),
+            ["rounds", rounds, reason @ ..] => {
+                (rounds.parse().ok().map(BanTimeout::Rounds), reason)
+            }
+            ["seconds", seconds, reason @ ..] => {
+                (seconds.parse().ok().map(BanTimeout::Seconds), reason)
+            }
+            _ => (None, &[][..]),
+        };
+        let (timeout, reason) = match (timeout, reason) {
+            (Some(timeout), []) => (timeout, String::new()),
+            (Some(timeout), [reason]) => (timeout, reason.to_string()),
+            _ => return status("InvalidArguments"),
+        };
+
+        self.lists
+            .bans
+            .retain(|ban| ban.id_type != id_type || ban.id != id);
+        self.lists.bans.push(BanEntry {
+            id_type,
+            id: id.to_owned(),
+            timeout,
+            reason,
+        });
+
+        // Banned players are removed from the server right away.
+        let banned: Vec<_> = self
+            .players
+            .iter()
+            .filter(|player| match id_type {
+                IdType::Name => player.name == id,
+                IdType::Guid => player.guid == id,
+                IdType::Ip => false,
+            })
+            .map(|player| player.name.clone())
+            .collect();
+        for name in banned {
+            self.leave(&name, events);
+        }
+
+        ok()
+    }
+
+    fn handle_var(&mut self, name: VarName, args: &[String]) -> Vec<String> {
+        if args.is_empty() {
+            let requests = match self.vars.get(&name) {
+                Some(var) => var.clone().into_requests(),
+                None => return status("UnknownCommand"),
+            };
+
+            // Variables set with one request per value, like the team factions, report every
+            // value at once.
+            let mut response = ok();
+            match requests.as_slice() {
+                [request] => response.extend(request[1..].iter().cloned()),
+                requests => response.extend(
+                    requests
+                        .iter()
+                        .filter_map(|request| request.last().cloned()),
+                ),
+            }
+
+            return response;
+        }
+
+        if name.is_startup_only() {
+            return status("CommandIsReadOnly");
+        }
+
+        let var = match (name, args, self.vars.get(&name)) {
+            (
+                VarName::TeamFactionOverride,
+                [team_id, faction],
+                Some(Var::TeamFactionOverride(factions)),
+            ) => {
+                let mut factions = *factions;
+                match (team_id.parse::<usize>(), faction.parse::<Faction>()) {
+                    (Ok(team_id), Ok(faction)) if (1..=4).contains(&team_id) => {
+                        factions[team_id - 1] = faction;
+                        Var::TeamFactionOverride(factions)
+                    }
+                    _ => return status("InvalidArguments"),
+                }
+            }
+            (VarName::TeamFactionOverride, _, _) => return status("InvalidArguments"),
+            _ => match Var::from_words(name, args.to_vec()) {
+                Ok(var) => var,
+                Err(_) => return status("InvalidArguments"),
+            },
+        };
+        if var.validate().is_err() {
+            return status("InvalidArguments");
+        }
+        if let Var::GamePassword(ref password) = var {
+            let ranked =
+                self.vars.get(&VarName::ServerType) == Some(&Var::ServerType(ServerType::Ranked));
+            if ranked && !password.is_empty() {
+                return status("InvalidConfig");
+            }
+        }
+
+        self.vars.insert(name, var);
+        ok()
+    }
+
+    fn copy_list(&mut self, command: &str, save: bool) {
+        let (from, to) = if save {
+            (&self.lists, &mut self.saved_lists)
+        } else {
+            (&self.saved_lists, &mut self.lists)
+        };
+
+        match command.split('.').next() {
+            Some("mapList") => to.maps = from.maps.clone(),
+            Some("banList") => to.bans = from.bans.clone(),
+            Some("reservedSlotsList") => to.reserved_slots = from.reserved_slots.clone(),
+            Some("spectatorList") => to.spectators = from.spectators.clone(),
+            Some("gameAdmin") => to.game_admins = from.game_admins.clone(),
+            _ => {}
+        }
+    }
+}
+
+fn default_var(name: VarName) -> Var {
+    match name {
+        VarName::ThreeDSpotting => Var::ThreeDSpotting(true),
+        VarName::ThirdPersonCam => Var::ThirdPersonCam(true),
+        VarName::AlwaysAllowSpectators => Var::AlwaysAllowSpectators(false),
+        VarName::AutoBalance => Var::AutoBalance(true),
+        VarName::SkillBasedBalance => Var::SkillBasedBalance(false),
+        VarName::BulletDamage => Var::BulletDamage(100),
+        VarName::Commander => Var::Commander(true),
+        VarName::CrossHair => Var::CrossHair(true),
+        VarName::ForceReloadWholeMags => Var::ForceReloadWholeMags(false),
+        VarName::FriendlyFire => Var::FriendlyFire(false),
+        VarName::GameModeCounter => Var::GameModeCounter(100),
+        VarName::GamePassword => Var::GamePassword(String::new()),
+        VarName::GunMasterWeaponsPreset => Var::GunMasterWeaponsPreset(0),
+        VarName::HitIndicatorsEnabled => Var::HitIndicatorsEnabled(true),
+        VarName::Hud => Var::Hud(true),
+        VarName::IdleBanRounds => Var::IdleBanRounds(0),
+        VarName::IdleTimeout => Var::IdleTimeout(300),
+        VarName::IsNoobOnlyJoin => Var::IsNoobOnlyJoin(false),
+        VarName::KillCam => Var::KillCam(true),
+        VarName::MaxPlayers => Var::MaxPlayers(64),
+        VarName::MaxSpectators => Var::MaxSpectators(4),
+        VarName::MiniMap => Var::MiniMap(true),
+        VarName::MiniMapSpotting => Var::MiniMapSpotting(true),
+        VarName::MpExperience => Var::MpExperience(String::new()),
+        VarName::NameTag => Var::NameTag(true),
+        VarName::OnlySquadLeaderSpawn => Var::OnlySquadLeaderSpawn(false),
+        VarName::PlayerRespawnTime => Var::PlayerRespawnTime(100),
+        VarName::Preset => Var::Preset(Preset {
+            kind: PresetKind::Normal,
+            locked: false,
+        }),
+        VarName::RegenerateHealth => Var::RegenerateHealth(true),
+        VarName::RoundLockdownCountdown => Var::RoundLockdownCountdown(15),
+        VarName::RoundPlayersReadyBypassTimer => Var::RoundPlayersReadyBypassTimer(250),
+        VarName::RoundPlayersReadyMinCount => Var::RoundPlayersReadyMinCount(1),
+        VarName::RoundPlayersReadyPercent => Var::RoundPlayersReadyPercent(70),
+        VarName::RoundRestartPlayerCount => Var::RoundRestartPlayerCount(2),
+        VarName::RoundStartPlayerCount => Var::RoundStartPlayerCount(4),
+        VarName::RoundTimeLimit => Var::RoundTimeLimit(100),
+        VarName::RoundWarmupTimeout => Var::RoundWarmupTimeout(30),
+        VarName::ServerDescription => Var::ServerDescription(String::new()),
+        VarName::ServerMessage => Var::ServerMessage(String::new()),
+        VarName::ServerName => Var::ServerName("maui simulator".to_owned()),
+        VarName::ServerType => Var::ServerType(ServerType::Ranked),
+        VarName::SoldierHealth => Var::SoldierHealth(100),
+        VarName::TeamFactionOverride => {
+            Var::TeamFactionOverride([Faction::Us, Faction::Ru, Faction::Us, Faction::Ru])
+        }
+        VarName::TeamKillCountForKick => Var::TeamKillCountForKick(0),
+        VarName::TeamKillKickForBan => Var::TeamKillKickForBan(0),
+        VarName::TeamKillValueDecreasePerSecond => Var::TeamKillValueDecreasePerSecond(1.0),
+        VarName::TeamKillValueForKick => Var::TeamKillValueForKick(0.0),
+        VarName::TeamKillValueIncrease => Var::TeamKillValueIncrease(1.0),
+        VarName::TicketBleedRate => Var::TicketBleedRate(100),
+        VarName::UnlockMode => Var::UnlockMode(UnlockMode::All),
+        VarName::VehicleSpawnAllowed => Var::VehicleSpawnAllowed(true),
+        VarName::VehicleSpawnDelay => Var::VehicleSpawnDelay(100),
+    }
+}
+
+/// Encodes players the way `admin.listPlayers` and several events do, headed by the field names.
+fn player_block(players: &[PlayerInfo]) -> Vec<String> {
+    let mut block = vec![PLAYER_FIELDS.len().to_string()];
+    block.extend(PLAYER_FIELDS.iter().map(|field| field.to_string()));
+    block.push(players.len().to_string());
+
+    for player in players {
+        let kind = match player.kind {
+            PlayerKind::Player => "0",
+            PlayerKind::Spectator => "1",
+            PlayerKind::Commander => "2",
+            PlayerKind::MobileCommander => "3",
+        };
+
+        block.extend(vec![
+            player.name.clone(),
+            player.guid.clone(),
+            player.team_id.to_string(),
+            player.squad_id.to_string(),
+            player.kills.to_string(),
+            player.deaths.to_string(),
+            player.score.to_string(),
+            player.rank.to_string(),
+            player.ping.to_string(),
+            kind.to_owned(),
+        ]);
+    }
+
+    block
+}
+
+fn ban_words(ban: &BanEntry) -> Vec<String> {
+    let id_type = match ban.id_type {
+        IdType::Name => "name",
+        IdType::Ip => "ip",
+        IdType::Guid => "guid",
+    };
+    let (ban_type, seconds, rounds) = match ban.timeout {
+        BanTimeout::Permanent => ("perm", 0, 0),
+        BanTimeout::Rounds(rounds) => ("rounds", 0, rounds),
+        BanTimeout::Seconds(seconds) => ("seconds", seconds, 0),
+    };
+
+    vec![
+        id_type.to_owned(),
+        ban.id.clone(),
+        ban_type.to_owned(),
+        seconds.to_string(),
+        rounds.to_string(),
+        ban.reason.clone(),
+    ]
+}
+
+fn add_name(names: &mut Vec<String>, name: &str) -> Vec<String> {
+    if names.iter().any(|other| other == name) {
+        return status("PlayerAlreadyInList");
+    }
+
+    names.push(name.to_owned());
+    ok()
+}
+
+fn remove_name(names: &mut Vec<String>, name: &str) -> Vec<String> {
+    match names.iter().position(|other| other == name) {
+        Some(index) => {
+            names.remove(index);
+            ok()
+        }
+        None => status("PlayerNotInList"),
+    }
+}
+
+fn list_names(names: &[String], offset: &[&str]) -> Vec<String> {
+    match parse_offset(offset) {
+        Some(offset) => {
+            let mut response = ok();
+            response.extend(names.iter().skip(offset).take(100).cloned());
+            response
+        }
+        None => status("InvalidArguments"),
+    }
+}
+
+fn parse_offset(args: &[&str]) -> Option<usize> {
+    match args {
+        [] => Some(0),
+        [offset] => offset.parse().ok(),
+        _ => None,
+    }
+}
+
+fn words(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
+
+fn ok() -> Vec<String> {
+    status("OK")
+}
+
+fn status(status: &str) -> Vec<String> {
+    vec![status.to_owned()]
+}
@@ -0,0 +1,76 @@
+use maui::{
+    models::{ServerType, Var, VarName},
+    testing::Simulator,
+    Client,
+};
+
+#[tokio::test]
+async fn apply_skips_startup_only_vars() {
+    let source = Simulator::start().await.unwrap();
+    source.set_var(Var::ServerType(ServerType::Unranked));
+    let target = Simulator::start().await.unwrap();
+
+    let source_client = Client::connect(source.addr()).await.unwrap();
+    source_client
+        .var_set(Var::FriendlyFire(true))
+        .await
+        .unwrap();
+    source_client
+        .var_set(Var::GamePassword("secret".to_owned()))
+        .await
+        .unwrap();
+    source_client.maps_clear().await.unwrap();
+    source_client
+        .maps_add("MP_Siege".to_owned(), "Domination0".to_owned(), 3, None)
+        .await
+        .unwrap();
+    source_client
+        .reserved_slots_add("alice".to_owned())
+        .await
+        .unwrap();
+    let config = source_client.config_snapshot().await.unwrap();
+
+    let target_client = Client::connect(target.addr()).await.unwrap();
+    target_client.config_apply(&config).await.unwrap();
+
+    assert_eq!(
+        target_client.var_get(VarName::FriendlyFire).await.unwrap(),
+        Var::FriendlyFire(true)
+    );
+    assert_eq!(
+        target_client.var_get(VarName::ServerType).await.unwrap(),
+        Var::ServerType(ServerType::Ranked)
+    );
+    assert_eq!(
+        target_client.var_get(VarName::GamePassword).await.unwrap(),
+        Var::GamePassword(String::new())
+    );
+    assert_eq!(
+        target_client.maps_get().await.unwrap(),
+        vec![("MP_Siege".to_owned(), "Domination0".to_owned(), 3)]
+    );
+    assert_eq!(
+        target_client.reserved_slots_get().await.unwrap(),
+        vec!["alice".to_owned()]
+    );
+}
+
+#[tokio::test]
+async fn apply_sets_password_on_unranked_server() {
+    let target = Simulator::start().await.unwrap();
+    target.set_var(Var::ServerType(ServerType::Unranked));
+    let client = Client::connect(target.addr()).await.unwrap();
+
+    let mut config = client.config_snapshot().await.unwrap();
+    for var in &mut config.vars {
+        if let Var::GamePassword(password) = var {
+            *password = "secret".to_owned();
+        }
+    }
+    client.config_apply(&config).await.unwrap();
+
+    assert_eq!(
+        client.var_get(VarName::GamePassword).await.unwrap(),
+        Var::GamePassword("secret".to_owned())
+    );
+}
@@ -0,0 +1,439 @@
+use std::{net::SocketAddr, time::Duration};
+
+use futures_util::{SinkExt, StreamExt};
+use maui::{
+    models::Event,
+    protocol::{Codec, Packet, Word, WordDecoding},
+    Client, ErrorKind,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    time,
+};
+use tokio_util::codec::Framed;
+
+/// Remote host driven packet by packet.
+struct Peer {
+    stream: Framed<TcpStream, Codec>,
+}
+
+impl Peer {
+    async fn listen() -> (TcpListener, SocketAddr) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        (listener, addr)
+    }
+
+    async fn accept(listener: &TcpListener) -> Peer {
+        let (tcp_stream, _) = listener.accept().await.unwrap();
+        Peer {
+            stream: Framed::new(tcp_stream, Codec::new()),
+        }
+    }
+
+    async fn recv(&mut self) -> Option<Packet> {
+        self.stream.next().await.map(Result::unwrap)
+    }
+
+    async fn send(&mut self, id: u32, is_response: bool, is_from_server: bool, words: &[&str]) {
+        let words = words
+            .iter()
+            .map(|&word| Word::from(word.to_owned()))
+            .collect();
+        self.send_packet(Packet::new(id, is_response, is_from_server, words))
+            .await;
+    }
+
+    async fn send_packet(&mut self, packet: Packet) {
+        self.stream.send(packet).await.unwrap();
+    }
+
+    /// Answers the next request with the words.
+    async fn respond(&mut self, words: &[&str]) -> Packet {
+        let request = self.recv().await.unwrap();
+        assert!(!request.is_response);
+        self.send(request.id, true, false, words).await;
+        request
+    }
+}
+
+#[tokio::test]
+async fn unexpected_packets() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(Client::connect(addr), Peer::accept(&listener));
+    let client = client.unwrap();
+    let mut events = client.subscribe();
+
+    peer.send(9, false, false, &["bogus"]).await;
+    peer.send(77, true, false, &["OK", "late"]).await;
+    let (response, _) = tokio::join!(
+        client.send(vec!["version".to_owned()]),
+        peer.respond(&["OK", "BF4", "1"])
+    );
+    assert_eq!(response.unwrap(), ["BF4", "1"]);
+
+    let envelope = events.recv().await.unwrap();
+    assert_eq!(envelope.words, ["bogus"]);
+    match envelope.event {
+        Event::UnexpectedPacket {
+            id,
+            is_response,
+            words,
+        } => {
+            assert_eq!(id, 9);
+            assert!(!is_response);
+            assert_eq!(words, ["bogus"]);
+        }
+        event => panic!("unexpected event: {:?}", event),
+    }
+
+    match events.recv().await.unwrap().event {
+        Event::UnexpectedPacket {
+            id,
+            is_response,
+            words,
+        } => {
+            assert_eq!(id, 77);
+            assert!(is_response);
+            assert_eq!(words, ["OK", "late"]);
+        }
+        event => panic!("unexpected event: {:?}", event),
+    }
+    assert!(!client.is_closed());
+}
+
+#[tokio::test]
+async fn cancelled_request_is_forgotten() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(Client::connect(addr), Peer::accept(&listener));
+    let client = client.unwrap();
+    let mut events = client.subscribe();
+
+    let (response, request) = tokio::join!(
+        client.send_timeout(vec!["version".to_owned()], Duration::from_millis(50)),
+        peer.recv()
+    );
+    assert_eq!(response.unwrap_err().kind(), ErrorKind::Timeout);
+    time::sleep(Duration::from_millis(50)).await;
+
+    // The late answer no longer matches a pending request.
+    let request = request.unwrap();
+    peer.send(request.id, true, false, &["OK", "BF4", "1"])
+        .await;
+    let envelope = time::timeout(Duration::from_secs(1), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    match envelope.event {
+        Event::UnexpectedPacket { id, .. } => assert_eq!(id, request.id),
+        event => panic!("unexpected event: {:?}", event),
+    }
+}
+
+#[tokio::test]
+async fn max_in_flight_holds_requests() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(
+        Client::builder()
+            .max_in_flight(Some(2))
+            .request_queue(Some(1))
+            .connect(addr),
+        Peer::accept(&listener)
+    );
+    let client = client.unwrap();
+
+    let requests: Vec<_> = (0..4)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move { client.send(vec![i.to_string()]).await })
+        })
+        .collect();
+
+    let first = peer.recv().await.unwrap();
+    let second = peer.recv().await.unwrap();
+    assert!(time::timeout(Duration::from_millis(100), peer.recv())
+        .await
+        .is_err());
+
+    // Every answer lets exactly one more request through.
+    peer.send(first.id, true, false, &["OK", "first"]).await;
+    let third = peer.recv().await.unwrap();
+    assert!(time::timeout(Duration::from_millis(100), peer.recv())
+        .await
+        .is_err());
+
+    peer.send(second.id, true, false, &["OK", "second"]).await;
+    let fourth = peer.recv().await.unwrap();
+    peer.send(third.id, true, false, &["OK", "third"]).await;
+    peer.send(fourth.id, true, false, &["OK", "fourth"]).await;
+
+    let mut responses = Vec::new();
+    for request in requests {
+        responses.extend(request.await.unwrap().unwrap());
+    }
+    responses.sort();
+    assert_eq!(responses, ["first", "fourth", "second", "third"]);
+}
+
+#[tokio::test]
+async fn cancelled_request_frees_slot() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(
+        Client::builder().max_in_flight(Some(1)).connect(addr),
+        Peer::accept(&listener)
+    );
+    let client = client.unwrap();
+
+    let cancelled = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .send_timeout(vec!["first".to_owned()], Duration::from_millis(100))
+                .await
+        }
+    });
+    assert_eq!(peer.recv().await.unwrap().words, ["first"]);
+    let queued = tokio::spawn({
+        let client = client.clone();
+        async move { client.send(vec!["second".to_owned()]).await }
+    });
+
+    // The queued request is only sent once the first one has been given up on.
+    let second = peer.recv().await.unwrap();
+    assert_eq!(second.words, ["second"]);
+    assert_eq!(
+        cancelled.await.unwrap().unwrap_err().kind(),
+        ErrorKind::Timeout
+    );
+
+    peer.send(second.id, true, false, &["OK"]).await;
+    assert!(queued.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn heartbeat_detects_silent_peer() {
+    let interval = Duration::from_millis(50);
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(
+        Client::builder().heartbeat(Some(interval), 3).connect(addr),
+        Peer::accept(&listener)
+    );
+    let client = client.unwrap();
+    let start = time::Instant::now();
+
+    time::timeout(Duration::from_secs(1), client.closed())
+        .await
+        .unwrap();
+    // The first heartbeat is sent after one interval, and declared missed on each of the next
+    // three.
+    assert!(start.elapsed() >= interval * 3);
+    assert_eq!(peer.recv().await.unwrap().words, ["version"]);
+
+    let err = client.send(vec!["version".to_owned()]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionLost);
+}
+
+#[tokio::test]
+async fn heartbeat_keeps_answered_connection() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(
+        Client::builder()
+            .heartbeat(Some(Duration::from_millis(20)), 2)
+            .connect(addr),
+        Peer::accept(&listener)
+    );
+    let client = client.unwrap();
+
+    for _ in 0..5 {
+        let request = peer.respond(&["OK", "BF4", "1"]).await;
+        assert_eq!(request.words, ["version"]);
+    }
+    assert!(!client.is_closed());
+}
+
+#[tokio::test]
+async fn shutdown_drains_then_quits() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(Client::connect(addr), Peer::accept(&listener));
+    let client = client.unwrap();
+
+    let request = tokio::spawn({
+        let client = client.clone();
+        async move { client.send(vec!["first".to_owned()]).await }
+    });
+    let first = peer.recv().await.unwrap();
+    let shutdown = tokio::spawn({
+        let client = client.clone();
+        async move { client.shutdown().await }
+    });
+
+    // Nothing is sent until the request in flight has been answered.
+    assert!(time::timeout(Duration::from_millis(100), peer.recv())
+        .await
+        .is_err());
+    let err = client.send(vec!["late".to_owned()]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionLost);
+
+    peer.send(first.id, true, false, &["OK"]).await;
+    assert!(request.await.unwrap().is_ok());
+    assert_eq!(peer.respond(&["OK"]).await.words, ["quit"]);
+
+    shutdown.await.unwrap().unwrap();
+    assert!(client.is_closed());
+    assert_eq!(peer.recv().await, None);
+}
+
+#[tokio::test]
+async fn shutdown_times_out_on_unanswered_quit() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(Client::connect(addr), Peer::accept(&listener));
+    let mut client = client.unwrap();
+    client.set_timeout(Some(Duration::from_millis(100)));
+
+    let (result, quit) = tokio::join!(client.shutdown(), peer.recv());
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::Timeout);
+    assert_eq!(quit.unwrap().words, ["quit"]);
+
+    time::timeout(Duration::from_secs(1), client.closed())
+        .await
+        .unwrap();
+    assert_eq!(peer.recv().await, None);
+}
+
+#[tokio::test]
+async fn shutdown_closes_on_unanswered_quit_with_heartbeat() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(
+        Client::builder()
+            .heartbeat(Some(Duration::from_millis(50)), 2)
+            .connect(addr),
+        Peer::accept(&listener)
+    );
+    let client = client.unwrap();
+
+    // Neither quit nor the heartbeats are answered, so the connection is declared lost.
+    let (result, quit) = tokio::join!(
+        time::timeout(Duration::from_secs(1), client.shutdown()),
+        peer.recv()
+    );
+    result.unwrap().unwrap();
+    assert_eq!(quit.unwrap().words, ["quit"]);
+    assert!(client.is_closed());
+}
+
+#[tokio::test]
+async fn dropped_shutdown_closes() {
+    let (listener, addr) = Peer::listen().await;
+    let (client, mut peer) = tokio::join!(Client::connect(addr), Peer::accept(&listener));
+    let client = client.unwrap();
+
+    let request = tokio::spawn({
+        let client = client.clone();
+        async move { client.send(vec!["first".to_owned()]).await }
+    });
+    peer.recv().await.unwrap();
+    let shutdown = tokio::spawn({
+        let client = client.clone();
+        async move { client.shutdown().await }
+    });
+    time::sleep(Duration::from_millis(50)).await;
+
+    // Giving up on the shutdown abandons the request still in flight.
+    shutdown.abort();
+    time::timeout(Duration::from_secs(1), client.closed())
+        .await
+        .unwrap();
+    assert_eq!(
+        request.await.unwrap().unwrap_err().kind(),
+        ErrorKind::ConnectionLost
+    );
+    assert_eq!(peer.recv().await, None);
+}
+
+fn latin1_words(words: &[&str]) -> Vec<Word> {
+    words
+        .iter()
+        .map(|&word| Word::from(word.chars().map(|c| c as u8).collect::<Vec<_>>()))
+        .collect()
+}
+
+async fn decoding_peer(decoding: WordDecoding) -> (Client, Peer) {
+    let (listener, addr) = Peer::listen().await;
+    let (client, peer) = tokio::join!(
+        Client::builder().word_decoding(decoding).connect(addr),
+        Peer::accept(&listener)
+    );
+    (client.unwrap(), peer)
+}
+
+/// Answers the next request with the words encoded as Latin-1.
+async fn respond_latin1(peer: &mut Peer, words: &[&str]) {
+    let request = peer.recv().await.unwrap();
+    peer.send_packet(Packet::new(request.id, true, false, latin1_words(words)))
+        .await;
+}
+
+#[tokio::test]
+async fn decode_response() {
+    for (decoding, expected) in [
+        (WordDecoding::Lossy, "b\u{fffd}b"),
+        (WordDecoding::Latin1, "béb"),
+    ] {
+        let (client, mut peer) = decoding_peer(decoding).await;
+
+        let (response, _) = tokio::join!(
+            client.send(vec!["player".to_owned()]),
+            respond_latin1(&mut peer, &["OK", "béb"])
+        );
+        assert_eq!(response.unwrap(), [expected]);
+    }
+}
+
+#[tokio::test]
+async fn strict_response_fails_request() {
+    let (client, mut peer) = decoding_peer(WordDecoding::Strict).await;
+
+    let (response, _) = tokio::join!(
+        client.send(vec!["player".to_owned()]),
+        respond_latin1(&mut peer, &["OK", "béb"])
+    );
+    assert_eq!(response.unwrap_err().kind(), ErrorKind::Parse);
+
+    // Only the request answered by the undecodable word fails.
+    let (response, _) = tokio::join!(
+        client.send(vec!["version".to_owned()]),
+        peer.respond(&["OK", "BF4", "1"])
+    );
+    assert_eq!(response.unwrap(), ["BF4", "1"]);
+}
+
+#[tokio::test]
+async fn decode_event() {
+    for decoding in [
+        WordDecoding::Strict,
+        WordDecoding::Lossy,
+        WordDecoding::Latin1,
+    ] {
+        let (client, mut peer) = decoding_peer(decoding).await;
+        let mut events = client.subscribe();
+
+        let words = latin1_words(&["player.onSpawn", "béb", "1"]);
+        peer.send_packet(Packet::new(0, false, true, words.clone()))
+            .await;
+        let envelope = events.recv().await.unwrap();
+        assert_eq!(envelope.words, words);
+
+        match (decoding, envelope.event) {
+            (WordDecoding::Strict, Event::Unknown { name, words }) => {
+                assert_eq!(name, "player.onSpawn");
+                assert_eq!(words, ["b\u{fffd}b", "1"]);
+            }
+            (WordDecoding::Lossy, Event::PlayerOnSpawn { name, .. }) => {
+                assert_eq!(name, "b\u{fffd}b")
+            }
+            (WordDecoding::Latin1, Event::PlayerOnSpawn { name, .. }) => assert_eq!(name, "béb"),
+            (_, event) => panic!("unexpected event: {:?}", event),
+        }
+        assert!(!client.is_closed());
+    }
+}
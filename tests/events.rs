@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use maui::{models::Event, testing::Simulator, Client, EventStream, Received};
+use tokio::time;
+
+async fn next_event(stream: &mut EventStream) -> Event {
+    match time::timeout(Duration::from_secs(1), stream.next()).await {
+        Ok(Some(Received::Event(envelope))) => envelope.event,
+        other => panic!("expected an event, got {:?}", other),
+    }
+}
+
+async fn assert_empty(stream: &mut EventStream) {
+    if let Ok(item) = time::timeout(Duration::from_millis(100), stream.next()).await {
+        panic!("unexpected item: {:?}", item);
+    }
+}
+
+#[tokio::test]
+async fn filters() {
+    let sim = Simulator::start().await.unwrap();
+    sim.player_join("alice");
+    sim.player_join("bob");
+    let client = Client::builder()
+        .events_enable(true)
+        .connect(sim.addr())
+        .await
+        .unwrap();
+
+    let mut kills = client.events().kills();
+    let mut chat = client.events().chat();
+    let mut chat_from_bob = client.events().chat_from("bob".to_owned());
+    let mut gg = client
+        .events()
+        .chat()
+        .filter(|event| matches!(event, Event::PlayerOnChat { message, .. } if message == "gg"));
+
+    sim.player_chat("alice", "hey");
+    sim.player_kill("bob", "alice", "U_ACR", false);
+    sim.player_chat("bob", "gg");
+    sim.player_chat("alice", "gg");
+
+    match next_event(&mut kills).await {
+        Event::PlayerOnKill { killer, victim, .. } => {
+            assert_eq!(killer, "bob");
+            assert_eq!(victim, "alice");
+        }
+        event => panic!("unexpected event: {:?}", event),
+    }
+    assert_empty(&mut kills).await;
+
+    for expected in ["hey", "gg", "gg"] {
+        match next_event(&mut chat).await {
+            Event::PlayerOnChat { message, .. } => assert_eq!(message, expected),
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+    assert_empty(&mut chat).await;
+
+    match next_event(&mut chat_from_bob).await {
+        Event::PlayerOnChat {
+            source, message, ..
+        } => {
+            assert_eq!(source, "bob");
+            assert_eq!(message, "gg");
+        }
+        event => panic!("unexpected event: {:?}", event),
+    }
+    assert_empty(&mut chat_from_bob).await;
+
+    // Filters are combined, so only messages passing every filter are yielded.
+    for expected in ["bob", "alice"] {
+        match next_event(&mut gg).await {
+            Event::PlayerOnChat { source, .. } => assert_eq!(source, expected),
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+    assert_empty(&mut gg).await;
+}
+
+#[tokio::test]
+async fn lagged() {
+    let sim = Simulator::start().await.unwrap();
+    sim.player_join("alice");
+    let client = Client::builder()
+        .events_enable(true)
+        .event_capacity(4)
+        .connect(sim.addr())
+        .await
+        .unwrap();
+    let mut events = client.events();
+    let mut kills = client.events().kills();
+
+    for i in 0..10 {
+        sim.player_chat("alice", &i.to_string());
+    }
+    // Give the client time to receive every event before the stream is polled.
+    time::sleep(Duration::from_millis(100)).await;
+
+    match time::timeout(Duration::from_secs(1), events.next()).await {
+        Ok(Some(Received::Lagged(count))) => assert_eq!(count, 6),
+        other => panic!("expected lag, got {:?}", other),
+    }
+    for expected in 6..10 {
+        match time::timeout(Duration::from_secs(1), events.next()).await {
+            Ok(Some(Received::Event(envelope))) => {
+                assert_eq!(envelope.sequence, expected);
+                match envelope.event {
+                    Event::PlayerOnChat { message, .. } => {
+                        assert_eq!(message, expected.to_string())
+                    }
+                    event => panic!("unexpected event: {:?}", event),
+                }
+            }
+            other => panic!("expected an event, got {:?}", other),
+        }
+    }
+    assert_empty(&mut events).await;
+
+    // Lag is reported even if none of the dropped events would have passed the filters.
+    match time::timeout(Duration::from_secs(1), kills.next()).await {
+        Ok(Some(Received::Lagged(count))) => assert_eq!(count, 6),
+        other => panic!("expected lag, got {:?}", other),
+    }
+    assert_empty(&mut kills).await;
+
+    drop(client);
+    assert!(events.next().await.is_none());
+}
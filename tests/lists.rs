@@ -0,0 +1,130 @@
+use maui::{
+    models::{BanTimeout, GameAdmin, IdType},
+    testing::{MockServer, Simulator},
+    Client, ErrorKind,
+};
+
+async fn add_bans(client: &Client, ids: std::ops::Range<usize>) {
+    for i in ids {
+        client
+            .bans_add(
+                IdType::Name,
+                format!("player{}", i),
+                BanTimeout::Permanent,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+}
+
+fn ban_list_requests(server: &MockServer) -> Vec<Vec<String>> {
+    server
+        .requests()
+        .into_iter()
+        .filter(|request| request[0] == "banList.list")
+        .collect()
+}
+
+#[tokio::test]
+async fn bans_get_full_page() {
+    let sim = Simulator::start().await.unwrap();
+    let client = Client::connect(sim.addr()).await.unwrap();
+    add_bans(&client, 0..100).await;
+
+    let bans = client.bans_get().await.unwrap();
+    assert_eq!(bans.len(), 100);
+    assert_eq!(bans[99].id, "player99");
+    assert_eq!(
+        ban_list_requests(sim.server()),
+        [["banList.list", "0"], ["banList.list", "100"]]
+    );
+}
+
+#[tokio::test]
+async fn bans_get_pages() {
+    let sim = Simulator::start().await.unwrap();
+    let client = Client::connect(sim.addr()).await.unwrap();
+    add_bans(&client, 0..99).await;
+
+    assert_eq!(client.bans_get().await.unwrap().len(), 99);
+    assert_eq!(ban_list_requests(sim.server()), [["banList.list", "0"]]);
+
+    add_bans(&client, 99..101).await;
+    let bans = client.bans_get().await.unwrap();
+    assert_eq!(bans.len(), 101);
+    assert_eq!(bans[100].id, "player100");
+}
+
+#[tokio::test]
+async fn bans_get_truncated_entry() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(
+        "banList.list",
+        ["OK", "name", "alice", "perm", "0", "0", "", "name", "bob"]
+            .iter()
+            .map(|&word| word.to_owned())
+            .collect(),
+    );
+    let client = Client::connect(server.addr()).await.unwrap();
+
+    let err = client.bans_get().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Parse);
+}
+
+#[tokio::test]
+async fn maps_get_invalid_words_per_map() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(
+        "mapList.list",
+        ["OK", "1", "2", "MP_Prison", "ConquestLarge0"]
+            .iter()
+            .map(|&word| word.to_owned())
+            .collect(),
+    );
+    let client = Client::connect(server.addr()).await.unwrap();
+
+    let err = client.maps_get().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Parse);
+}
+
+#[tokio::test]
+async fn game_admins_get() {
+    let sim = Simulator::start().await.unwrap();
+    let client = Client::connect(sim.addr()).await.unwrap();
+    client
+        .game_admins_add(GameAdmin {
+            name: "alice".to_owned(),
+            restriction_level: 0,
+        })
+        .await
+        .unwrap();
+    client
+        .game_admins_add(GameAdmin {
+            name: "bob".to_owned(),
+            restriction_level: 3,
+        })
+        .await
+        .unwrap();
+
+    let admins = client.game_admins_get().await.unwrap();
+    assert_eq!(admins.len(), 2);
+    assert_eq!(admins[0].name, "alice");
+    assert_eq!(admins[1].restriction_level, 3);
+}
+
+#[tokio::test]
+async fn game_admins_get_truncated_entry() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(
+        "gameAdmin.list",
+        ["OK", "alice", "0", "bob"]
+            .iter()
+            .map(|&word| word.to_owned())
+            .collect(),
+    );
+    let client = Client::connect(server.addr()).await.unwrap();
+
+    let err = client.game_admins_get().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Parse);
+}
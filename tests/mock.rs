@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use maui::{
+    protocol::{Packet, Word},
+    testing::{MockServer, Simulator},
+    Client, ErrorKind,
+};
+use tokio::time;
+
+#[tokio::test]
+async fn events_require_enabling() {
+    let server = MockServer::start().await.unwrap();
+    let client = Client::connect(server.addr()).await.unwrap();
+    let mut events = client.subscribe();
+    server.wait_for_clients(1).await;
+
+    let event = vec![
+        "player.onSpawn".to_owned(),
+        "alice".to_owned(),
+        "1".to_owned(),
+    ];
+    assert_eq!(server.push_event(event.clone()), 0);
+    assert!(time::timeout(Duration::from_millis(100), events.recv())
+        .await
+        .is_err());
+
+    client.events_enable().await.unwrap();
+    assert_eq!(server.push_event(event), 1);
+    let envelope = events.recv().await.unwrap();
+    assert_eq!(envelope.words, ["player.onSpawn", "alice", "1"]);
+}
+
+#[tokio::test]
+async fn login_without_password() {
+    let server = MockServer::start().await.unwrap();
+    let client = Client::connect(server.addr()).await.unwrap();
+
+    let err = client.login("secret".to_owned()).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Status);
+    assert_eq!(err.status(), Some("PasswordNotSet"));
+
+    let err = client.login_hashed("secret".to_owned()).await.unwrap_err();
+    assert_eq!(err.status(), Some("PasswordNotSet"));
+}
+
+#[tokio::test]
+async fn public_commands() {
+    let server = MockServer::start().await.unwrap();
+    server.set_password(Some("secret".to_owned()));
+    server.respond("listPlayers", vec!["OK".to_owned()]);
+    server.respond("admin.listPlayers", vec!["OK".to_owned()]);
+    let client = Client::connect(server.addr()).await.unwrap();
+
+    assert!(client.send(vec!["listPlayers".to_owned()]).await.is_ok());
+    let err = client
+        .send(vec!["admin.listPlayers".to_owned()])
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some("LogInRequired"));
+
+    client.login_hashed("secret".to_owned()).await.unwrap();
+    assert!(client
+        .send(vec!["admin.listPlayers".to_owned()])
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn drop_closes_sessions() {
+    let sim = Simulator::start().await.unwrap();
+    let client = Client::connect(sim.addr()).await.unwrap();
+    sim.server().wait_for_clients(1).await;
+    assert!(client.get_server_info().await.is_ok());
+
+    drop(sim);
+    time::timeout(Duration::from_secs(1), client.closed())
+        .await
+        .unwrap();
+    assert!(client.get_server_info().await.is_err());
+}
+
+#[tokio::test]
+async fn events_are_acknowledged() {
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder()
+        .events_enable(true)
+        .connect(server.addr())
+        .await
+        .unwrap();
+    let mut events = client.subscribe();
+
+    for name in ["alice", "bob"] {
+        server.push_event(vec![
+            "player.onSpawn".to_owned(),
+            name.to_owned(),
+            "1".to_owned(),
+        ]);
+        events.recv().await.unwrap();
+    }
+    // Acknowledgements are sent before the events are delivered, so they have been received by
+    // the time a later request is answered.
+    client.send(vec!["version".to_owned()]).await.unwrap();
+
+    assert_eq!(
+        server.responses(),
+        [
+            Packet::new(0, true, true, vec![Word::from_static("OK")]),
+            Packet::new(1, true, true, vec![Word::from_static("OK")]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn events_without_acknowledgement() {
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder()
+        .events_enable(true)
+        .acknowledge_events(false)
+        .connect(server.addr())
+        .await
+        .unwrap();
+    let mut events = client.subscribe();
+
+    server.push_event(vec![
+        "player.onSpawn".to_owned(),
+        "alice".to_owned(),
+        "1".to_owned(),
+    ]);
+    events.recv().await.unwrap();
+    client.send(vec!["version".to_owned()]).await.unwrap();
+
+    assert!(server.responses().is_empty());
+}
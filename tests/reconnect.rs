@@ -0,0 +1,174 @@
+use std::{net::SocketAddr, time::Duration};
+
+use maui::{testing::MockServer, Client, ConnectionState, ErrorKind, ReconnectPolicy};
+use tokio::{sync::broadcast, time};
+
+fn policy() -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial_backoff: Duration::from_millis(20),
+        max_backoff: Duration::from_millis(50),
+        max_attempts: None,
+    }
+}
+
+/// Starts a server at the address of a dropped one, whose listener may take a moment to close.
+async fn restart(addr: SocketAddr) -> MockServer {
+    loop {
+        match MockServer::bind(addr).await {
+            Ok(server) => return server,
+            Err(_) => time::sleep(Duration::from_millis(10)).await,
+        }
+    }
+}
+
+async fn next_state(state_rx: &mut broadcast::Receiver<ConnectionState>) -> ConnectionState {
+    time::timeout(Duration::from_secs(5), state_rx.recv())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+async fn wait_for_state(
+    state_rx: &mut broadcast::Receiver<ConnectionState>,
+    state: ConnectionState,
+) {
+    while next_state(state_rx).await != state {}
+}
+
+fn event(name: &str) -> Vec<String> {
+    vec!["player.onSpawn".to_owned(), name.to_owned(), "1".to_owned()]
+}
+
+#[tokio::test]
+async fn restores_session() {
+    let server = MockServer::start().await.unwrap();
+    server.set_password(Some("secret".to_owned()));
+    let addr = server.addr();
+    let client = Client::builder()
+        .login("secret".to_owned())
+        .events_enable(true)
+        .reconnect(policy())
+        .connect(addr)
+        .await
+        .unwrap();
+    let mut events = client.subscribe();
+    let mut state_rx = client.subscribe_state();
+
+    assert_eq!(server.push_event(event("alice")), 1);
+    assert_eq!(
+        events.recv().await.unwrap().words,
+        ["player.onSpawn", "alice", "1"]
+    );
+
+    drop(server);
+    assert_eq!(
+        next_state(&mut state_rx).await,
+        ConnectionState::Disconnected
+    );
+    assert_eq!(
+        next_state(&mut state_rx).await,
+        ConnectionState::Reconnecting { attempt: 1 }
+    );
+
+    let server = restart(addr).await;
+    server.set_password(Some("secret".to_owned()));
+    wait_for_state(&mut state_rx, ConnectionState::Connected).await;
+
+    let requests = server.requests();
+    assert_eq!(requests[0], ["login.hashed"]);
+    assert_eq!(requests[1][0], "login.hashed");
+    assert_eq!(requests[2], ["admin.eventsEnabled", "true"]);
+
+    // Subscribers from before the disconnect keep receiving events.
+    assert_eq!(server.push_event(event("bob")), 1);
+    assert_eq!(
+        events.recv().await.unwrap().words,
+        ["player.onSpawn", "bob", "1"]
+    );
+    assert!(!client.is_closed());
+}
+
+#[tokio::test]
+async fn sends_stashed_requests() {
+    let server = MockServer::start().await.unwrap();
+    let addr = server.addr();
+    let client = Client::builder()
+        .reconnect(policy())
+        .connect(addr)
+        .await
+        .unwrap();
+    let mut state_rx = client.subscribe_state();
+
+    drop(server);
+    wait_for_state(&mut state_rx, ConnectionState::Reconnecting { attempt: 1 }).await;
+
+    let request = tokio::spawn({
+        let client = client.clone();
+        async move { client.send(vec!["version".to_owned()]).await }
+    });
+    // Give the request time to be stashed before the server comes back.
+    time::sleep(Duration::from_millis(50)).await;
+
+    let server = restart(addr).await;
+    assert_eq!(request.await.unwrap().unwrap(), ["BF4", "0"]);
+    assert_eq!(server.requests(), [["version"]]);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder()
+        .reconnect(ReconnectPolicy {
+            max_attempts: Some(2),
+            ..policy()
+        })
+        .connect(server.addr())
+        .await
+        .unwrap();
+    let mut state_rx = client.subscribe_state();
+
+    drop(server);
+    assert_eq!(
+        next_state(&mut state_rx).await,
+        ConnectionState::Disconnected
+    );
+    assert_eq!(
+        next_state(&mut state_rx).await,
+        ConnectionState::Reconnecting { attempt: 1 }
+    );
+    assert_eq!(
+        next_state(&mut state_rx).await,
+        ConnectionState::Reconnecting { attempt: 2 }
+    );
+
+    time::timeout(Duration::from_secs(5), client.closed())
+        .await
+        .unwrap();
+    let err = client.send(vec!["version".to_owned()]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionLost);
+}
+
+#[tokio::test(start_paused = true)]
+async fn large_backoff() {
+    let server = MockServer::start().await.unwrap();
+    let client = Client::builder()
+        .reconnect(ReconnectPolicy {
+            initial_backoff: Duration::MAX,
+            max_backoff: Duration::MAX,
+            max_attempts: Some(2),
+        })
+        .connect(server.addr())
+        .await
+        .unwrap();
+    let mut state_rx = client.subscribe_state();
+
+    drop(server);
+    // Time is paused, so the backoff elapses as soon as nothing else is left to do.
+    let reconnecting = async {
+        while state_rx.recv().await.unwrap() != (ConnectionState::Reconnecting { attempt: 2 }) {}
+    };
+    tokio::select! {
+        _ = reconnecting => {}
+        _ = client.closed() => panic!("client closed before the second attempt"),
+    }
+}